@@ -1,3 +1,6 @@
 fn main() {
-    tauri_build::build()
+    // Sem a janela (`--no-default-features`) não há contexto do Tauri para gerar
+    if std::env::var_os("CARGO_FEATURE_APP").is_some() {
+        tauri_build::build()
+    }
 }
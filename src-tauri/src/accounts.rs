@@ -0,0 +1,234 @@
+use std::path::PathBuf;
+
+use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use crate::auth::{is_invalid_grant, is_token_fresh, request_token_refresh};
+use crate::config::{config_dir, set_drive_folder};
+use crate::drive::{parse_response, GoogleTokens};
+use crate::error::CloudTrayError;
+use crate::retry::send_with_retry;
+use crate::token_store;
+use crate::GoogleCredentials;
+
+static REGISTRY_LOCK: Mutex<()> = Mutex::const_new(());
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Account {
+	pub email: String,
+	pub name: Option<String>,
+	pub picture: Option<String>,
+	/// Escopos do Drive concedidos além do drive.file, para avisar o usuário
+	#[serde(default)]
+	pub broader_scopes: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct AccountRegistry {
+	pub active: Option<String>,
+	pub accounts: Vec<Account>,
+}
+
+#[derive(Debug, Deserialize)]
+struct UserInfo {
+	email: String,
+	name: Option<String>,
+	picture: Option<String>,
+}
+
+fn registry_path() -> Result<PathBuf, CloudTrayError> {
+	Ok(config_dir()?.join("accounts.json"))
+}
+
+async fn read_registry() -> Result<AccountRegistry, CloudTrayError> {
+	let path = registry_path()?;
+	if !path.exists() {
+		return Ok(AccountRegistry::default());
+	}
+
+	let registry_str = tokio::fs::read_to_string(&path)
+		.await
+		.map_err(|e| CloudTrayError::from(e).with_context("Erro ao ler contas"))?;
+
+	serde_json::from_str(&registry_str)
+		.map_err(|e| CloudTrayError::config(format!("Erro ao parsear contas: {}", e)))
+}
+
+async fn write_registry(registry: &AccountRegistry) -> Result<(), CloudTrayError> {
+	let path = registry_path()?;
+	if let Some(parent) = path.parent() {
+		tokio::fs::create_dir_all(parent)
+			.await
+			.map_err(|e| CloudTrayError::from(e).with_context("Erro ao criar diretório de configuração"))?;
+	}
+
+	let registry_json = serde_json::to_string_pretty(registry)
+		.map_err(|e| CloudTrayError::internal(format!("Erro ao serializar contas: {}", e)))?;
+
+	tokio::fs::write(&path, registry_json)
+		.await
+		.map_err(|e| CloudTrayError::from(e).with_context("Erro ao salvar contas"))
+}
+
+/// Busca o e-mail da conta dona do token, usado como identificador da conta.
+pub async fn fetch_account(client: &reqwest::Client, userinfo_url: &str, access_token: &str) -> Result<Account, CloudTrayError> {
+	let mut headers = HeaderMap::new();
+	headers.insert(AUTHORIZATION, HeaderValue::from_str(&format!("Bearer {}", access_token)).unwrap());
+
+	let request = client
+		.get(userinfo_url)
+		.headers(headers);
+	let response = send_with_retry(request, "buscar dados da conta").await?;
+
+	let user_info: UserInfo = parse_response(response, "buscar dados da conta").await?;
+
+	Ok(Account {
+		email: user_info.email,
+		name: user_info.name,
+		picture: user_info.picture,
+		broader_scopes: Vec::new(),
+	})
+}
+
+/// Dono do token pela API do Drive. Logins antigos só pediram o escopo drive.file, que não dá acesso
+/// ao userinfo, mas permite ler o `about`.
+async fn fetch_drive_account(client: &reqwest::Client, about_url: &str, access_token: &str) -> Result<Account, CloudTrayError> {
+	let mut headers = HeaderMap::new();
+	headers.insert(AUTHORIZATION, HeaderValue::from_str(&format!("Bearer {}", access_token)).unwrap());
+
+	let request = client
+		.get(about_url)
+		.headers(headers)
+		.query(&[("fields", "user(emailAddress,displayName,photoLink)")]);
+	let response = send_with_retry(request, "buscar dados da conta").await?;
+
+	#[derive(Debug, Deserialize)]
+	#[serde(rename_all = "camelCase")]
+	struct DriveUser {
+		email_address: String,
+		display_name: Option<String>,
+		photo_link: Option<String>,
+	}
+
+	#[derive(Debug, Deserialize)]
+	struct About {
+		user: DriveUser,
+	}
+
+	let about: About = parse_response(response, "buscar dados da conta").await?;
+
+	Ok(Account {
+		email: about.user.email_address,
+		name: about.user.display_name,
+		picture: about.user.photo_link,
+		broader_scopes: Vec::new(),
+	})
+}
+
+/// Registra (ou atualiza) a conta, salva seus tokens e a torna ativa.
+pub async fn register_account(account: Account, tokens: &GoogleTokens) -> Result<(), CloudTrayError> {
+	token_store::store_tokens(&account.email, tokens).await?;
+
+	let _guard = REGISTRY_LOCK.lock().await;
+	let mut registry = read_registry().await?;
+	registry.active = Some(account.email.clone());
+	match registry.accounts.iter_mut().find(|existing| existing.email == account.email) {
+		Some(existing) => *existing = account,
+		None => registry.accounts.push(account),
+	}
+	write_registry(&registry).await
+}
+
+/// Versões anteriores guardavam uma única conta sem identificador; na primeira execução ela vira
+/// uma conta registrada pelo e-mail.
+async fn migrate_legacy_account(credentials: &GoogleCredentials, client: &reqwest::Client) -> Result<Option<String>, CloudTrayError> {
+	let Some(mut tokens) = token_store::load_legacy_tokens().await? else {
+		return Ok(None);
+	};
+
+	if !is_token_fresh(&tokens) {
+		tokens = match request_token_refresh(tokens.refresh_token, credentials, client).await {
+			Ok(tokens) => tokens,
+			// Tokens antigos revogados não servem para migrar; sem apagá-los, toda chamada tentaria de novo
+			Err(err) if is_invalid_grant(&err) => {
+				token_store::delete_legacy_tokens().await?;
+				return Err(err);
+			}
+			Err(err) => return Err(err),
+		};
+	}
+
+	let about_url = credentials.endpoints.lock().unwrap().about_url();
+	let account = fetch_drive_account(client, &about_url, &tokens.access_token).await?;
+	let email = account.email.clone();
+	register_account(account, &tokens).await?;
+
+	// Os tokens já estão no token_store; o arquivo antigo (às vezes em texto puro) não pode ficar para trás
+	token_store::delete_legacy_tokens().await?;
+
+	Ok(Some(email))
+}
+
+/// Resolve a conta a ser usada: a informada pelo frontend ou, na falta dela, a conta ativa.
+pub async fn resolve_account_id(
+	account_id: Option<String>,
+	credentials: &GoogleCredentials,
+	client: &reqwest::Client,
+) -> Result<String, CloudTrayError> {
+	let registry = read_registry().await?;
+
+	if let Some(account_id) = account_id {
+		if registry.accounts.iter().any(|account| account.email == account_id) {
+			return Ok(account_id);
+		}
+		return Err(CloudTrayError::not_found(format!("Conta não encontrada: {}", account_id)));
+	}
+
+	if let Some(active) = registry.active {
+		return Ok(active);
+	}
+
+	if registry.accounts.is_empty() {
+		if let Some(migrated) = migrate_legacy_account(credentials, client).await? {
+			return Ok(migrated);
+		}
+	}
+
+	Err(CloudTrayError::not_authenticated("Tokens não encontrados. Faça login primeiro."))
+}
+
+/// Remove a conta e seus tokens; se era a ativa, a próxima conta registrada passa a ser a ativa.
+pub async fn remove_account_data(account_id: &str, credentials: &GoogleCredentials) -> Result<AccountRegistry, CloudTrayError> {
+	credentials.cached_tokens.lock().unwrap().remove(account_id);
+	token_store::delete_tokens(account_id).await?;
+	set_drive_folder(account_id, None).await?;
+
+	let _guard = REGISTRY_LOCK.lock().await;
+	let mut registry = read_registry().await?;
+	registry.accounts.retain(|account| account.email != account_id);
+	if registry.active.as_deref() == Some(account_id) {
+		registry.active = registry.accounts.first().map(|account| account.email.clone());
+	}
+	write_registry(&registry).await?;
+
+	Ok(registry)
+}
+
+pub async fn list_accounts() -> Result<AccountRegistry, CloudTrayError> {
+	read_registry().await
+}
+
+pub async fn switch_account(account_id: String) -> Result<AccountRegistry, CloudTrayError> {
+	let _guard = REGISTRY_LOCK.lock().await;
+	let mut registry = read_registry().await?;
+
+	if !registry.accounts.iter().any(|account| account.email == account_id) {
+		return Err(CloudTrayError::not_found(format!("Conta não encontrada: {}", account_id)));
+	}
+
+	registry.active = Some(account_id);
+	write_registry(&registry).await?;
+
+	Ok(registry)
+}
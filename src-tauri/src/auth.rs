@@ -1,181 +1,590 @@
-use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use rand::distributions::Alphanumeric;
+use rand::Rng;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use url::Url;
 
-use tauri_plugin_oauth::start;
+use crate::accounts::{fetch_account, register_account, remove_account_data, resolve_account_id, Account};
+use crate::diagnostics;
+use crate::drive::{GoogleDriveProvider, GoogleTokens};
+use crate::error::CloudTrayError;
+use crate::http_client::HttpClient;
+use crate::retry::send_with_retry;
+use crate::token_store;
+use crate::GoogleCredentials;
 
-use tauri::command;
+// Renova o access token alguns minutos antes de expirar
+const TOKEN_REFRESH_MARGIN_SECS: i64 = 5 * 60;
 
-use crate::drive::GoogleTokens;
+// Só o drive.file: o app enxerga apenas a pasta e os arquivos que ele mesmo criou
+const GOOGLE_SCOPE: &str = "openid email https://www.googleapis.com/auth/drive.file";
+const DRIVE_FILE_SCOPE: &str = "https://www.googleapis.com/auth/drive.file";
+const DRIVE_SCOPE_PREFIX: &str = "https://www.googleapis.com/auth/drive";
 
-use crate::GoogleCredentials;
-use tauri::State;
-
-#[command]
-pub async fn start_oauth_server(window: tauri::Window) -> Result<u16, String> {
-		start(move |url| {
-				let _ = window.show();
-				let _ = window.set_focus();
-				std::thread::sleep(std::time::Duration::from_millis(100));
-				let _ = window.emit("oauth_callback", url.clone());
-		})
-		.map_err(|err| err.to_string())
+const DEVICE_CODE_GRANT_TYPE: &str = "urn:ietf:params:oauth:grant-type:device_code";
+const DEFAULT_DEVICE_POLL_INTERVAL_SECS: u64 = 5;
+// O servidor pede para desacelerar com `slow_down`; o intervalo cresce 5 segundos a cada pedido (RFC 8628)
+const SLOW_DOWN_INCREMENT: Duration = Duration::from_secs(5);
+
+pub struct PendingAuthorization {
+	state: String,
+	code_verifier: String,
+	redirect_uri: String,
+}
+
+/// Código do fluxo de dispositivo aguardando a aprovação do usuário.
+pub struct PendingDeviceAuthorization {
+	device_code: String,
+	interval: Duration,
+	expires_at: Instant,
+}
+
+/// O que o usuário precisa para aprovar o login em outro aparelho: abrir `verification_url` e digitar `user_code`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DeviceAuthorization {
+	#[serde(skip_serializing)]
+	device_code: String,
+	pub user_code: String,
+	#[serde(alias = "verification_uri")]
+	pub verification_url: String,
+	pub expires_in: u64,
+	#[serde(default)]
+	pub interval: Option<u64>,
+}
+
+/// Avisa que a conta precisa de um novo login. `reason` é o código do OAuth (ex.: `invalid_grant`).
+#[derive(Debug, Clone, Serialize)]
+pub struct AuthRequired {
+	pub account_id: String,
+	pub reason: String,
+	pub message: String,
+}
+
+pub type AuthRequiredCallback = Box<dyn Fn(AuthRequired) + Send + Sync>;
+
+fn random_string(length: usize) -> String {
+	rand::thread_rng()
+		.sample_iter(&Alphanumeric)
+		.take(length)
+		.map(char::from)
+		.collect()
+}
+
+fn pkce_challenge(code_verifier: &str) -> String {
+	URL_SAFE_NO_PAD.encode(Sha256::digest(code_verifier.as_bytes()))
+}
+
+/// Parâmetros do cliente OAuth; o client_secret só é enviado quando configurado, já que o PKCE o torna opcional.
+fn client_form(credentials: &GoogleCredentials) -> Vec<(&'static str, String)> {
+	let mut form = vec![("client_id", credentials.client_id.lock().unwrap().clone())];
+
+	let client_secret = credentials.client_secret.lock().unwrap().clone();
+	if !client_secret.is_empty() {
+		form.push(("client_secret", client_secret));
+	}
+
+	form
+}
+
+/// Confere o parâmetro `state` da URL de retorno do OAuth com o da autorização pendente.
+/// Se não bater, a autorização pendente é descartada.
+pub fn verify_callback_state(credentials: &GoogleCredentials, url: &str) -> Result<(), CloudTrayError> {
+		let expected_state = credentials
+				.pending_authorization
+				.lock()
+				.unwrap()
+				.as_ref()
+				.map(|pending| pending.state.clone());
+
+		let received_state = Url::parse(url).ok().and_then(|parsed| {
+				parsed
+						.query_pairs()
+						.find(|(key, _)| key == "state")
+						.map(|(_, value)| value.into_owned())
+		});
+
+		match (expected_state, received_state) {
+				(Some(expected), Some(received)) if expected == received => Ok(()),
+				_ => {
+						*credentials.pending_authorization.lock().unwrap() = None;
+						Err(CloudTrayError::authorization_failed("Parâmetro state inválido na resposta do OAuth"))
+				}
+		}
+}
+
+pub async fn build_authorization_url(
+	redirect_uri: String,
+	credentials: &GoogleCredentials
+) -> Result<String, CloudTrayError> {
+		let client_id = credentials.client_id.lock().unwrap().clone();
+		let auth_url = credentials.endpoints.lock().unwrap().auth_url.clone();
+		if client_id.is_empty() {
+				return Err(CloudTrayError::config("Credenciais do Google não configuradas"));
+		}
+
+		let state = random_string(32);
+		let code_verifier = random_string(64);
+		let code_challenge = pkce_challenge(&code_verifier);
+
+		let url = Url::parse_with_params(
+				&auth_url,
+				&[
+						("client_id", client_id.as_str()),
+						("redirect_uri", redirect_uri.as_str()),
+						("response_type", "code"),
+						("scope", GOOGLE_SCOPE),
+						("access_type", "offline"),
+						("prompt", "consent"),
+						("state", state.as_str()),
+						("code_challenge", code_challenge.as_str()),
+						("code_challenge_method", "S256"),
+				],
+		)
+		.map_err(|e| CloudTrayError::internal(format!("Erro ao montar URL de autorização: {}", e)))?;
+
+		*credentials.pending_authorization.lock().unwrap() = Some(PendingAuthorization {
+				state,
+				code_verifier,
+				redirect_uri,
+		});
+
+		Ok(url.to_string())
 }
 
-#[command]
 pub async fn exchange_auth_code(
 	code: String, 
-	redirect_uri: String,
-	credentials: State<'_, GoogleCredentials>
-) -> Result<GoogleTokens, String> {
-		let client = reqwest::Client::new();
+	credentials: &GoogleCredentials,
+	http: &HttpClient
+) -> Result<GoogleTokens, CloudTrayError> {
+		let pending = credentials
+				.pending_authorization
+				.lock()
+				.unwrap()
+				.take()
+				.ok_or_else(|| CloudTrayError::authorization_failed("Nenhuma autorização pendente"))?;
 
-    let client_id = credentials.client_id.lock().unwrap().clone();
-    let client_secret = credentials.client_secret.lock().unwrap().clone();
+		let mut form = client_form(credentials);
+		form.extend([
+				("code", code),
+				("code_verifier", pending.code_verifier),
+				("redirect_uri", pending.redirect_uri),
+				("grant_type", String::from("authorization_code")),
+		]);
 		
-		let response = client
-				.post("https://oauth2.googleapis.com/token")
-				.form(&[
-						("client_id", &client_id),
-						("client_secret", &client_secret),
-						("code", &code),
-						("redirect_uri", &redirect_uri),
-						("grant_type", &String::from("authorization_code")),
-				])
+		let token_url = credentials.endpoints.lock().unwrap().token_url.clone();
+		let response = http
+				.client()
+				.post(token_url)
+				.form(&form)
 				.send()
 				.await
-				.map_err(|e| format!("Erro na requisição: {}", e))?;
+				.map_err(|e| CloudTrayError::from(e).with_context("Erro na requisição"))?;
 
+		let status = response.status();
 		let response_text = response.text().await
-				.map_err(|e| format!("Erro ao ler resposta: {}", e))?;
+				.map_err(|e| CloudTrayError::from(e).with_context("Erro ao ler resposta"))?;
+
+		if !status.is_success() {
+				return Err(token_endpoint_error(status, &response_text));
+		}
 		
-		serde_json::from_str(&response_text)
-				.map_err(|e| format!("Erro ao parsear JSON: {} - Response: {}", e, response_text))
-}
-
-#[command]
-pub async fn get_tokens(credentials: State<'_, GoogleCredentials>) -> Result<GoogleTokens, String> {
-	let config_dir = tauri::api::path::app_config_dir(&tauri::Config::default())
-			.ok_or("Não foi possível encontrar o diretório de configuração")?;
-	let config_path = config_dir.join("config");
-	let tokens_path = config_path.join("google_tokens.json");
-	
-	if !config_path.exists() {
-			tokio::fs::create_dir_all(&config_path)
-					.await
-					.map_err(|e| format!("Erro ao criar diretório de configuração: {}", e))?;
+		parse_token_response(&response_text)
+}
+
+fn parse_token_response(response_text: &str) -> Result<GoogleTokens, CloudTrayError> {
+	let mut tokens: GoogleTokens = serde_json::from_str(response_text)
+		.map_err(|e| CloudTrayError::unexpected_response(format!("Erro ao parsear JSON: {} - Response: {}", e, response_text)))?;
+
+	verify_granted_scopes(tokens.scope.as_deref())?;
+	tokens.expires_at = Utc::now().timestamp() + tokens.expires_in as i64;
+
+	Ok(tokens)
+}
+
+/// Na tela de consentimento o usuário pode desmarcar o acesso ao Drive; sem o drive.file o app não funciona.
+/// Respostas sem `scope` são aceitas, já que o campo é opcional no OAuth.
+fn verify_granted_scopes(scope: Option<&str>) -> Result<(), CloudTrayError> {
+	match scope {
+		Some(scope) if !scope.split_whitespace().any(|granted| granted == DRIVE_FILE_SCOPE) => Err(CloudTrayError::PermissionDenied {
+			status: None,
+			reason: Some("insufficient_scope".to_string()),
+			message: format!("O acesso aos arquivos do Google Drive não foi concedido (escopos: {})", scope),
+		}),
+		_ => Ok(()),
 	}
-	
-	if !tokens_path.exists() {
-			return Err("Tokens file not found. Please authenticate first.".to_string());
+}
+
+/// Escopos do Drive concedidos além do drive.file (ex.: autorizações antigas do mesmo client com acesso total).
+pub fn broader_scopes(scope: Option<&str>) -> Vec<String> {
+	scope
+		.unwrap_or_default()
+		.split_whitespace()
+		.filter(|granted| granted.starts_with(DRIVE_SCOPE_PREFIX) && *granted != DRIVE_FILE_SCOPE)
+		.map(String::from)
+		.collect()
+}
+
+/// Inicia o fluxo de autorização de dispositivo (RFC 8628), para sessões sem navegador como SSH e servidores de build.
+pub async fn start_device_authorization(
+	credentials: &GoogleCredentials,
+	http: &HttpClient,
+) -> Result<DeviceAuthorization, CloudTrayError> {
+	let client_id = credentials.client_id.lock().unwrap().clone();
+	if client_id.is_empty() {
+		return Err(CloudTrayError::config("Credenciais do Google não configuradas"));
 	}
-	
-	match tokio::fs::read_to_string(&tokens_path).await {
-			Ok(tokens_str) => {
-					let tokens: GoogleTokens = serde_json::from_str(&tokens_str)
-							.map_err(|e| format!("Erro ao parsear tokens JSON: {}", e))?;
-					
-					let client = reqwest::Client::new();
-					let mut headers = HeaderMap::new();
-					headers.insert(AUTHORIZATION, HeaderValue::from_str(&format!("Bearer {}", tokens.access_token)).unwrap());
-					
-					let test_response = client
-							.get("https://www.googleapis.com/drive/v3/files")
-							.headers(headers)
-							.send()
-							.await;
-
-					match test_response {
-							Ok(response) if response.status().is_success() => Ok(tokens),
-							_ => {
-									refresh_access_token(tokens.refresh_token, credentials).await
-							}
-					}
-			},
-			Err(e) => {
-					Err(format!("Error reading tokens file: {}", e))
-			}
+
+	let device_code_url = credentials.endpoints.lock().unwrap().device_code_url.clone();
+	let response = http
+		.client()
+		.post(device_code_url)
+		.form(&[("client_id", client_id.as_str()), ("scope", GOOGLE_SCOPE)])
+		.send()
+		.await
+		.map_err(|e| CloudTrayError::from(e).with_context("Erro ao solicitar código de dispositivo"))?;
+
+	let status = response.status();
+	let response_text = response.text().await
+		.map_err(|e| CloudTrayError::from(e).with_context("Erro ao ler resposta"))?;
+
+	if !status.is_success() {
+		return Err(token_endpoint_error(status, &response_text));
 	}
+
+	let device: DeviceAuthorization = serde_json::from_str(&response_text)
+		.map_err(|e| CloudTrayError::unexpected_response(format!("Erro ao parsear JSON: {}", e)))?;
+
+	*credentials.pending_device_authorization.lock().unwrap() = Some(PendingDeviceAuthorization {
+		device_code: device.device_code.clone(),
+		interval: Duration::from_secs(device.interval.unwrap_or(DEFAULT_DEVICE_POLL_INTERVAL_SECS)),
+		expires_at: Instant::now() + Duration::from_secs(device.expires_in),
+	});
+
+	Ok(device)
 }
 
-#[command]
-pub async fn save_tokens(tokens: GoogleTokens) -> Result<(), String> {
-		let config_dir = tauri::api::path::app_config_dir(&tauri::Config::default())
-				.ok_or("Não foi possível encontrar o diretório de configuração")?;
-		let config_path = config_dir.join("config");
-		let tokens_path = config_path.join("google_tokens.json");
-		
-		tokio::fs::create_dir_all(&config_path)
-				.await
-				.map_err(|e| format!("Erro ao criar diretório: {}", e))?;
-		
-		let tokens_json = serde_json::to_string_pretty(&tokens)
-				.map_err(|e| format!("Erro ao serializar tokens: {}", e))?;
-		
-		tokio::fs::write(&tokens_path, tokens_json)
-				.await
-				.map_err(|e| format!("Erro ao salvar arquivo: {}", e))?;
+/// Consulta o token endpoint até o usuário aprovar (ou negar) o código e salva os tokens como no `save_tokens`.
+pub async fn complete_device_authorization(
+	credentials: &GoogleCredentials,
+	http: &HttpClient,
+) -> Result<Account, CloudTrayError> {
+	let pending = credentials
+		.pending_device_authorization
+		.lock()
+		.unwrap()
+		.take()
+		.ok_or_else(|| CloudTrayError::authorization_failed("Nenhuma autorização de dispositivo pendente"))?;
+
+	let tokens = poll_device_token(pending, credentials, http).await?;
+	save_tokens(tokens, credentials, http).await
+}
+
+async fn poll_device_token(
+	pending: PendingDeviceAuthorization,
+	credentials: &GoogleCredentials,
+	http: &HttpClient,
+) -> Result<GoogleTokens, CloudTrayError> {
+	let mut form = client_form(credentials);
+	form.extend([
+		("device_code", pending.device_code),
+		("grant_type", String::from(DEVICE_CODE_GRANT_TYPE)),
+	]);
+
+	let token_url = credentials.endpoints.lock().unwrap().token_url.clone();
+	let mut interval = pending.interval;
+
+	loop {
+		tokio::time::sleep(interval).await;
+		if Instant::now() >= pending.expires_at {
+			return Err(CloudTrayError::authorization_failed("O código de dispositivo expirou; inicie o login novamente"));
+		}
+
+		let response = match http.client().post(&token_url).form(&form).send().await {
+			Ok(response) => response,
+			// Falhas de rede passageiras não encerram o login; a próxima consulta tenta de novo
+			Err(e) if e.is_timeout() || e.is_connect() => continue,
+			Err(e) => return Err(CloudTrayError::from(e).with_context("Erro na requisição")),
+		};
+
+		let status = response.status();
+		let response_text = response.text().await
+			.map_err(|e| CloudTrayError::from(e).with_context("Erro ao ler resposta"))?;
+
+		if status.is_success() {
+			return parse_token_response(&response_text);
+		}
+
+		match serde_json::from_str::<TokenErrorResponse>(&response_text) {
+			Ok(error) if error.error == "authorization_pending" => {}
+			Ok(error) if error.error == "slow_down" => interval += SLOW_DOWN_INCREMENT,
+			_ => return Err(token_endpoint_error(status, &response_text)),
+		}
+	}
+}
+
+pub async fn get_tokens(
+	account_id: Option<String>,
+	credentials: &GoogleCredentials,
+	http: &HttpClient
+) -> Result<GoogleTokens, CloudTrayError> {
+	let account_id = resolve_account_id(account_id, credentials, &http.client()).await?;
+
+	if let Some(tokens) = cached_valid_tokens(credentials, &account_id) {
+		return Ok(tokens);
+	}
+
+	// Evita que vários comandos simultâneos façam refresh ao mesmo tempo
+	let _refresh_guard = credentials.refresh_lock.lock().await;
+
+	if let Some(tokens) = cached_valid_tokens(credentials, &account_id) {
+		return Ok(tokens);
+	}
+
+	let cached_tokens = credentials.cached_tokens.lock().unwrap().get(&account_id).cloned();
+	let tokens = match cached_tokens {
+		Some(tokens) => tokens,
+		None => token_store::load_tokens(&account_id)
+			.await?
+			.ok_or_else(|| CloudTrayError::not_authenticated("Tokens não encontrados. Faça login primeiro."))?,
+	};
+
+	if is_token_fresh(&tokens) {
+		credentials.cached_tokens.lock().unwrap().insert(account_id, tokens.clone());
+		return Ok(tokens);
+	}
+
+	let new_tokens = match request_token_refresh(tokens.refresh_token, credentials, &http.client()).await {
+		Ok(new_tokens) => new_tokens,
+		Err(err) if is_invalid_grant(&err) => return Err(clear_revoked_tokens(&account_id, err, credentials).await),
+		Err(err) => return Err(err),
+	};
+	token_store::store_tokens(&account_id, &new_tokens).await?;
+	credentials.cached_tokens.lock().unwrap().insert(account_id, new_tokens.clone());
+
+	Ok(new_tokens)
+}
+
+/// O refresh token foi revogado, expirou ou perdeu a validade (ex.: senha trocada); só um novo login resolve.
+pub fn is_invalid_grant(err: &CloudTrayError) -> bool {
+	matches!(err, CloudTrayError::AuthorizationFailed { .. }) && err.reason() == Some("invalid_grant")
+}
+
+/// Descarta os tokens que o Google não aceita mais, para que as próximas chamadas não tentem o refresh de novo,
+/// e avisa quem estiver ouvindo (a janela do tray) que a conta precisa de login.
+async fn clear_revoked_tokens(account_id: &str, err: CloudTrayError, credentials: &GoogleCredentials) -> CloudTrayError {
+	credentials.cached_tokens.lock().unwrap().remove(account_id);
+	if let Err(delete_err) = token_store::delete_tokens(account_id).await {
+		diagnostics::warn(format!("Erro ao remover tokens revogados: {}", delete_err));
+	}
+
+	let err = err.with_context("Sessão expirada ou acesso revogado; faça login novamente");
+
+	if let Some(on_auth_required) = credentials.on_auth_required.lock().unwrap().as_ref() {
+		on_auth_required(AuthRequired {
+			account_id: account_id.to_string(),
+			reason: err.reason().unwrap_or_default().to_string(),
+			message: err.message().to_string(),
+		});
+	}
+
+	err
+}
+
+pub fn is_token_fresh(tokens: &GoogleTokens) -> bool {
+	tokens.expires_at - TOKEN_REFRESH_MARGIN_SECS > Utc::now().timestamp()
+}
+
+fn cached_valid_tokens(credentials: &GoogleCredentials, account_id: &str) -> Option<GoogleTokens> {
+	credentials
+		.cached_tokens
+		.lock()
+		.unwrap()
+		.get(account_id)
+		.filter(|tokens| is_token_fresh(tokens))
+		.cloned()
+}
+
+/// Salva os tokens de um novo login, registrando a conta pelo e-mail e tornando-a a conta ativa.
+pub async fn save_tokens(
+	tokens: GoogleTokens,
+	credentials: &GoogleCredentials,
+	http: &HttpClient
+) -> Result<Account, CloudTrayError> {
+		let userinfo_url = credentials.endpoints.lock().unwrap().userinfo_url.clone();
+		let mut account = fetch_account(&http.client(), &userinfo_url, &tokens.access_token).await?;
+
+		account.broader_scopes = broader_scopes(tokens.scope.as_deref());
+
+		register_account(account.clone(), &tokens).await?;
+
+		credentials.cached_tokens.lock().unwrap().insert(account.email.clone(), tokens);
 		
-		Ok(())
+		Ok(account)
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenErrorResponse {
+		error: String,
+		error_description: Option<String>,
+}
+
+/// O endpoint de token responde erros no formato do OAuth (`error`, `error_description`), não no envelope das APIs do Google.
+fn token_endpoint_error(status: reqwest::StatusCode, response_text: &str) -> CloudTrayError {
+		match serde_json::from_str::<TokenErrorResponse>(response_text) {
+				Ok(error) if status.is_client_error() => CloudTrayError::AuthorizationFailed {
+						status: Some(status.as_u16()),
+						message: error.error_description.unwrap_or_else(|| error.error.clone()),
+						reason: Some(error.error),
+				},
+				_ => CloudTrayError::from_status(status.as_u16(), None, response_text.to_string()),
+		}
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 struct RefreshTokenResponse {
 		access_token: String,
 		expires_in: i32,
-		scope: String,
+		scope: Option<String>,
 		token_type: String,
 }
 
-async fn refresh_access_token(
+pub async fn request_token_refresh(
 	refresh_token: String,
-	credentials: State<'_, GoogleCredentials>
-) -> Result<GoogleTokens, String> {
-		let client = reqwest::Client::new();
-
-    let client_id = credentials.client_id.lock().unwrap().clone();
-    let client_secret = credentials.client_secret.lock().unwrap().clone();
+	credentials: &GoogleCredentials,
+	client: &reqwest::Client
+) -> Result<GoogleTokens, CloudTrayError> {
+		let mut form = client_form(credentials);
+		form.extend([
+				("refresh_token", refresh_token.clone()),
+				("grant_type", String::from("refresh_token")),
+		]);
 		
+		let token_url = credentials.endpoints.lock().unwrap().token_url.clone();
 		let response = client
-				.post("https://oauth2.googleapis.com/token")
-				.form(&[
-						("client_id", &client_id),
-						("client_secret", &client_secret),
-						("refresh_token", &refresh_token),
-						("grant_type", &String::from("refresh_token")),
-				])
+				.post(token_url)
+				.form(&form)
 				.send()
 				.await
-				.map_err(|e| format!("Erro na requisição: {}", e))?;
+				.map_err(|e| CloudTrayError::from(e).with_context("Erro na requisição"))?;
 
+		let status = response.status();
 		let response_text = response.text().await
-				.map_err(|e| format!("Erro ao ler resposta: {}", e))?;
+				.map_err(|e| CloudTrayError::from(e).with_context("Erro ao ler resposta"))?;
+
+		if !status.is_success() {
+				return Err(token_endpoint_error(status, &response_text));
+		}
 		
 		let refresh_response: RefreshTokenResponse = serde_json::from_str(&response_text)
-				.map_err(|e| format!("Erro ao parsear JSON: {}", e))?;
+				.map_err(|e| CloudTrayError::unexpected_response(format!("Erro ao parsear JSON: {}", e)))?;
+
+		verify_granted_scopes(refresh_response.scope.as_deref())?;
 		
-		let new_tokens = GoogleTokens {
+		Ok(GoogleTokens {
 				access_token: refresh_response.access_token,
 				refresh_token,
 				expires_in: refresh_response.expires_in,
 				token_type: refresh_response.token_type,
-		};
-		
-		save_tokens(new_tokens.clone()).await?;
-		
-		Ok(new_tokens)
+				expires_at: Utc::now().timestamp() + refresh_response.expires_in as i64,
+				scope: refresh_response.scope,
+		})
 }
 
-#[command]
-pub async fn logout() -> Result<(), String> {
-		let config_dir = tauri::api::path::app_config_dir(&tauri::Config::default())
-				.ok_or("Não foi possível encontrar o diretório de configuração")?;
-		let tokens_path = config_dir.join("config").join("google_tokens.json");
-		
-		if tokens_path.exists() {
-				tokio::fs::remove_file(&tokens_path)
-						.await
-						.map_err(|e| format!("Erro ao remover arquivo de tokens: {}", e))?;
+/// Resultado do logout. `failures` lista o que não pôde ser desfeito no Google ou apagado do computador,
+/// inclusive o motivo de a pasta não ter ido para a lixeira.
+#[derive(Debug, Serialize, Default)]
+pub struct LogoutReport {
+	pub account_id: Option<String>,
+	pub tokens_revoked: bool,
+	pub folder_trashed: bool,
+	pub failures: Vec<CloudTrayError>,
+}
+
+/// Revoga um token no Google. Um token já inválido (expirado ou revogado antes) conta como revogado.
+async fn revoke_token(token: &str, credentials: &GoogleCredentials, http: &HttpClient) -> Result<(), CloudTrayError> {
+	let revoke_url = credentials.endpoints.lock().unwrap().revoke_url.clone();
+	let request = http.client().post(revoke_url).form(&[("token", token)]);
+	let response = send_with_retry(request, "revogar token").await?;
+
+	let status = response.status();
+	if status.is_success() {
+		return Ok(());
+	}
+
+	let response_text = response.text().await
+		.map_err(|e| CloudTrayError::from(e).with_context("Erro ao ler resposta"))?;
+
+	match serde_json::from_str::<TokenErrorResponse>(&response_text) {
+		Ok(error) if error.error == "invalid_token" => Ok(()),
+		_ => Err(token_endpoint_error(status, &response_text).with_context("Erro ao revogar token")),
+	}
+}
+
+/// Encerra a sessão da conta informada (ou da ativa); as demais contas continuam conectadas.
+/// Os tokens são revogados no Google e, com `trash_folder`, a pasta do CloudTray perde o link público e vai
+/// para a lixeira. Falhas nessas etapas e na remoção dos dados locais entram no relatório em vez de virar erro.
+pub async fn logout(
+	account_id: Option<String>,
+	trash_folder: bool,
+	credentials: &GoogleCredentials,
+	http: &HttpClient
+) -> Result<LogoutReport, CloudTrayError> {
+	*credentials.pending_authorization.lock().unwrap() = None;
+	*credentials.pending_device_authorization.lock().unwrap() = None;
+
+	// Sem nenhuma conta não há o que desfazer; conta desconhecida, registro ilegível ou migração sem rede
+	// são erros, para a conta não parecer desconectada sem ter sido revogada nem apagada
+	let account_id = match resolve_account_id(account_id.clone(), credentials, &http.client()).await {
+		Ok(account_id) => account_id,
+		Err(CloudTrayError::NotAuthenticated { .. }) if account_id.is_none() => return Ok(LogoutReport::default()),
+		Err(err) => return Err(err),
+	};
+
+	let mut report = LogoutReport {
+		account_id: Some(account_id.clone()),
+		..Default::default()
+	};
+
+	// A pasta precisa de um token válido, então vem antes da revogação
+	if trash_folder {
+		match GoogleDriveProvider::new(Some(account_id.clone()), credentials, http).trash_app_folder().await {
+			Ok(()) => report.folder_trashed = true,
+			Err(err) => report.failures.push(err),
 		}
-		
-		Ok(())
-}
\ No newline at end of file
+	}
+
+	let cached_tokens = credentials.cached_tokens.lock().unwrap().get(&account_id).cloned();
+	let tokens = match cached_tokens {
+		Some(tokens) => Some(tokens),
+		None => token_store::load_tokens(&account_id).await.unwrap_or_else(|err| {
+			report.failures.push(err);
+			None
+		}),
+	};
+
+	if let Some(tokens) = tokens {
+		let mut revoked = true;
+		for token in [&tokens.access_token, &tokens.refresh_token] {
+			if token.is_empty() {
+				continue;
+			}
+			if let Err(err) = revoke_token(token, credentials, http).await {
+				revoked = false;
+				report.failures.push(err);
+			}
+		}
+		report.tokens_revoked = revoked;
+	}
+
+	// Os tokens já foram revogados; um erro aqui não pode esconder o relatório
+	if let Err(err) = remove_account_data(&account_id, credentials).await {
+		report.failures.push(err);
+	}
+
+	Ok(report)
+}
@@ -0,0 +1,365 @@
+//! Interface de linha de comando do CloudTray: envia, lista e remove arquivos usando a mesma
+//! conta, pasta e configuração do tray, sem abrir a janela.
+
+use std::io::{BufRead, BufReader, IsTerminal, Write};
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::{Parser, Subcommand};
+use serde::Serialize;
+use serde_json::Value;
+use url::Url;
+
+use cloudtray_core::accounts::Account;
+use cloudtray_core::auth;
+use cloudtray_core::cli::{self, UploadResult};
+use cloudtray_core::config::{self, AppConfig};
+use cloudtray_core::diagnostics::{self, Warning};
+use cloudtray_core::endpoints::GoogleEndpoints;
+use cloudtray_core::error::CloudTrayError;
+use cloudtray_core::http_client::HttpClient;
+use cloudtray_core::progress::{ProgressCallback, UploadProgress};
+use cloudtray_core::storage;
+use cloudtray_core::GoogleCredentials;
+
+#[derive(Parser)]
+#[command(name = "cloudtray-cli", version, about = "Envia e compartilha arquivos pelo CloudTray a partir do terminal")]
+struct Cli {
+	/// Imprime o resultado em JSON
+	#[arg(long, global = true)]
+	json: bool,
+
+	/// Conta (e-mail) a usar; sem ela vale a conta ativa do tray
+	#[arg(long, global = true)]
+	account: Option<String>,
+
+	/// Client ID do OAuth do Google, necessário para login e renovação de tokens
+	#[arg(long, global = true, env = "CLOUDTRAY_GOOGLE_CLIENT_ID", hide_env_values = true)]
+	client_id: Option<String>,
+
+	#[arg(long, global = true, env = "CLOUDTRAY_GOOGLE_CLIENT_SECRET", hide_env_values = true)]
+	client_secret: Option<String>,
+
+	#[command(subcommand)]
+	command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+	/// Envia os arquivos para a pasta do CloudTray e imprime os links de compartilhamento
+	Upload {
+		#[arg(required = true)]
+		paths: Vec<PathBuf>,
+	},
+	/// Lista os arquivos recentes da pasta do CloudTray
+	List,
+	/// Remove um arquivo pelo id
+	Delete { id: String },
+	/// Conecta uma conta do Google pelo navegador
+	Login {
+		/// Login por código em outro aparelho, para sessões sem navegador (SSH, servidores de build)
+		#[arg(long)]
+		device: bool,
+	},
+	/// Desconecta a conta e revoga o acesso do CloudTray no Google
+	Logout {
+		/// Também remove o link público da pasta do CloudTray e a move para a lixeira
+		#[arg(long)]
+		trash_folder: bool,
+	},
+	/// Mostra ou troca a pasta de destino no Google Drive
+	Folder {
+		#[command(subcommand)]
+		action: FolderAction,
+	},
+	/// Lê ou altera o app_config.json
+	Config {
+		#[command(subcommand)]
+		action: ConfigAction,
+	},
+}
+
+#[derive(Subcommand)]
+enum FolderAction {
+	/// Lista as pastas do CloudTray que podem ser usadas como destino
+	List,
+	/// Troca o destino para outra pasta do CloudTray (id ou link do Drive)
+	Set { folder: String },
+}
+
+#[derive(Subcommand)]
+enum ConfigAction {
+	/// Mostra a configuração inteira ou só uma chave (ex.: network.proxy_url)
+	Get { key: Option<String> },
+	/// Altera uma chave; o valor é lido como JSON e, se não for JSON válido, como texto.
+	/// A pasta do Drive muda com `folder set`
+	Set { key: String, value: String },
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+	let cli = Cli::parse();
+	let json = cli.json;
+
+	match run(cli).await {
+		Ok(code) => code,
+		Err(err) => {
+			if json {
+				print_json(&cli::error_json(&err));
+			} else {
+				eprintln!("{}", err);
+			}
+			ExitCode::FAILURE
+		}
+	}
+}
+
+async fn run(cli: Cli) -> Result<ExitCode, CloudTrayError> {
+	// No terminal os avisos (ex.: retentativas) vão para o stderr, como o progresso
+	diagnostics::set_warning_listener(Some(Box::new(|warning: Warning| {
+		eprintln!("Aviso: {}", warning.message);
+	})));
+
+	let app_config = config::load_or_create_config().await?;
+
+	let credentials = GoogleCredentials::new(
+		cli.client_id.unwrap_or_default(),
+		cli.client_secret.unwrap_or_default(),
+		GoogleEndpoints::from_config(&app_config),
+	);
+	let http = HttpClient::new()?;
+	http.configure(&app_config.network).await?;
+
+	match cli.command {
+		Command::Upload { paths } => upload(paths, cli.account, &credentials, &http, cli.json).await,
+		Command::List => {
+			let files = storage::list_recent_files(cli.account, &credentials, &http).await?;
+			if cli.json {
+				print_json(&files);
+			} else {
+				for file in files {
+					println!("{}\t{}\t{}", file.id, file.name, file.web_view_link);
+				}
+			}
+			Ok(ExitCode::SUCCESS)
+		}
+		Command::Delete { id } => {
+			storage::delete_file(id.clone(), cli.account, &credentials, &http).await?;
+			if cli.json {
+				print_json(&serde_json::json!({ "deleted": id }));
+			}
+			Ok(ExitCode::SUCCESS)
+		}
+		Command::Login { device } => {
+			let account = if device {
+				device_login(&credentials, &http).await?
+			} else {
+				login(&credentials, &http).await?
+			};
+			if !account.broader_scopes.is_empty() {
+				eprintln!(
+					"Aviso: a conta concedeu mais acesso ao Drive do que o CloudTray precisa ({}); revise em https://myaccount.google.com/permissions",
+					account.broader_scopes.join(", ")
+				);
+			}
+			if cli.json {
+				print_json(&account);
+			} else {
+				println!("{}", account.email);
+			}
+			Ok(ExitCode::SUCCESS)
+		}
+		Command::Logout { trash_folder } => {
+			let report = auth::logout(cli.account, trash_folder, &credentials, &http).await?;
+			if cli.json {
+				print_json(&report);
+			} else {
+				for failure in &report.failures {
+					eprintln!("Aviso: {}", failure);
+				}
+			}
+			Ok(ExitCode::SUCCESS)
+		}
+		Command::Folder { action } => {
+			let folders = match action {
+				FolderAction::List => storage::list_drive_folders(cli.account, &credentials, &http).await?,
+				FolderAction::Set { folder } => vec![storage::select_drive_folder(folder, cli.account, &credentials, &http).await?],
+			};
+			if cli.json {
+				print_json(&folders);
+			} else {
+				for folder in folders {
+					println!("{}\t{}", folder.id, folder.name);
+				}
+			}
+			Ok(ExitCode::SUCCESS)
+		}
+		Command::Config { action } => {
+			config_command(action, app_config, &credentials, &http, cli.json).await?;
+			Ok(ExitCode::SUCCESS)
+		}
+	}
+}
+
+fn print_json<T: Serialize>(value: &T) {
+	println!("{}", serde_json::to_string_pretty(value).unwrap());
+}
+
+/// No terminal, o progresso vai para o stderr para não misturar com os links no stdout.
+fn terminal_progress() -> ProgressCallback {
+	if !std::io::stderr().is_terminal() {
+		return Box::new(|_| {});
+	}
+
+	Box::new(|progress: UploadProgress| {
+		eprint!("\r{}: {}%", progress.file_name, progress.progress);
+		if progress.progress >= 100 {
+			eprintln!();
+		}
+		let _ = std::io::stderr().flush();
+	})
+}
+
+async fn upload(
+	paths: Vec<PathBuf>,
+	account_id: Option<String>,
+	credentials: &GoogleCredentials,
+	http: &HttpClient,
+	json: bool,
+) -> Result<ExitCode, CloudTrayError> {
+	let folder = storage::get_or_create_app_folder(account_id.clone(), credentials, http).await?;
+
+	let mut results = Vec::new();
+	for path in paths {
+		let path = path.to_string_lossy().into_owned();
+		let result = storage::upload_file_path(
+			path.clone(),
+			folder.id.clone(),
+			account_id.clone(),
+			credentials,
+			http,
+			terminal_progress(),
+		).await;
+
+		if !json {
+			match &result {
+				Ok(file) => println!("{}", file.web_view_link),
+				Err(err) => eprintln!("{}: {}", path, err),
+			}
+		}
+		results.push(UploadResult::new(path, result));
+	}
+
+	if json {
+		print_json(&results);
+	}
+
+	Ok(cli::upload_exit_code(&results))
+}
+
+/// Login pelo navegador com redirecionamento para um servidor local temporário, como no tray.
+async fn login(credentials: &GoogleCredentials, http: &HttpClient) -> Result<Account, CloudTrayError> {
+	if credentials.client_id.lock().unwrap().is_empty() {
+		return Err(CloudTrayError::config("Informe --client-id ou CLOUDTRAY_GOOGLE_CLIENT_ID"));
+	}
+
+	let listener = TcpListener::bind("127.0.0.1:0")
+		.map_err(|e| CloudTrayError::from(e).with_context("Erro ao iniciar servidor local"))?;
+	let port = listener
+		.local_addr()
+		.map_err(|e| CloudTrayError::from(e).with_context("Erro ao iniciar servidor local"))?
+		.port();
+
+	let authorization_url = auth::build_authorization_url(format!("http://127.0.0.1:{}", port), credentials).await?;
+	eprintln!("Abra este endereço no navegador para autorizar o CloudTray:\n\n{}\n", authorization_url);
+
+	let callback_url = tokio::task::spawn_blocking(move || wait_for_callback(listener))
+		.await
+		.map_err(|e| CloudTrayError::internal(format!("Erro ao aguardar autorização: {}", e)))??;
+
+	auth::verify_callback_state(credentials, &callback_url)?;
+
+	let parsed = Url::parse(&callback_url)
+		.map_err(|e| CloudTrayError::authorization_failed(format!("Resposta do OAuth inválida: {}", e)))?;
+	let param = |name: &str| parsed.query_pairs().find(|(key, _)| key == name).map(|(_, value)| value.into_owned());
+
+	if let Some(error) = param("error") {
+		return Err(CloudTrayError::authorization_failed(format!("Autorização negada: {}", error)));
+	}
+	let code = param("code").ok_or_else(|| CloudTrayError::authorization_failed("Resposta do OAuth sem código"))?;
+
+	let tokens = auth::exchange_auth_code(code, credentials, http).await?;
+	auth::save_tokens(tokens, credentials, http).await
+}
+
+async fn device_login(credentials: &GoogleCredentials, http: &HttpClient) -> Result<Account, CloudTrayError> {
+	let device = auth::start_device_authorization(credentials, http).await?;
+	eprintln!(
+		"Em qualquer aparelho, acesse {} e informe o código {}\nAguardando a autorização...",
+		device.verification_url, device.user_code
+	);
+
+	auth::complete_device_authorization(credentials, http).await
+}
+
+/// Aguarda o redirecionamento do navegador e devolve a URL recebida.
+fn wait_for_callback(listener: TcpListener) -> Result<String, CloudTrayError> {
+	loop {
+		let (mut stream, _) = listener
+			.accept()
+			.map_err(|e| CloudTrayError::from(e).with_context("Erro ao aguardar autorização"))?;
+
+		let mut request_line = String::new();
+		BufReader::new(&stream)
+			.read_line(&mut request_line)
+			.map_err(|e| CloudTrayError::from(e).with_context("Erro ao ler resposta do navegador"))?;
+
+		// "GET /?state=...&code=... HTTP/1.1"; outras requisições (ex.: favicon) são ignoradas
+		let path = request_line.split_whitespace().nth(1).unwrap_or_default().to_string();
+		if !path.contains("state=") {
+			let _ = stream.write_all(b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n");
+			continue;
+		}
+
+		let body = "Autorização recebida. Você já pode fechar esta aba e voltar ao terminal.";
+		let _ = write!(
+			stream,
+			"HTTP/1.1 200 OK\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+			body.len(),
+			body
+		);
+
+		return Ok(format!("http://127.0.0.1{}", path));
+	}
+}
+
+async fn config_command(
+	action: ConfigAction,
+	app_config: AppConfig,
+	credentials: &GoogleCredentials,
+	http: &HttpClient,
+	json: bool,
+) -> Result<(), CloudTrayError> {
+	match action {
+		ConfigAction::Get { key } => {
+			let value = serde_json::to_value(&app_config)
+				.map_err(|e| CloudTrayError::internal(format!("Erro ao serializar configuração: {}", e)))?;
+			let selected = match key.as_deref() {
+				Some(key) => cli::config_value(&value, key)
+					.ok_or_else(|| CloudTrayError::not_found(format!("Chave de configuração não encontrada: {}", key)))?,
+				None => &value,
+			};
+
+			match selected {
+				Value::String(text) if !json => println!("{}", text),
+				other => print_json(other),
+			}
+			Ok(())
+		}
+		ConfigAction::Set { key, value } => {
+			let updated = cli::set_config_key(&app_config, &key, &value)?;
+			config::save_config(updated, credentials, http).await
+		}
+	}
+}
@@ -0,0 +1,99 @@
+//! Partes da `cloudtray-cli` que não dependem do terminal: o relatório do `upload`, a saída de
+//! erro do `--json` e o `config get/set` por chave. Ficam no núcleo para serem testadas.
+
+use std::process::ExitCode;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::config::AppConfig;
+use crate::drive::DriveFile;
+use crate::error::CloudTrayError;
+
+/// Resultado do envio de um arquivo na saída `--json`: `file` no sucesso, `error` na falha.
+#[derive(Debug, Serialize)]
+pub struct UploadResult {
+	pub path: String,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub file: Option<DriveFile>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub error: Option<CloudTrayError>,
+}
+
+impl UploadResult {
+	pub fn new(path: String, result: Result<DriveFile, CloudTrayError>) -> Self {
+		match result {
+			Ok(file) => Self { path, file: Some(file), error: None },
+			Err(err) => Self { path, file: None, error: Some(err) },
+		}
+	}
+}
+
+/// Uma falha não interrompe os demais arquivos; o código de saída indica se algum deles falhou.
+pub fn upload_exit_code(results: &[UploadResult]) -> ExitCode {
+	if results.iter().any(|result| result.error.is_some()) {
+		ExitCode::FAILURE
+	} else {
+		ExitCode::SUCCESS
+	}
+}
+
+/// Saída de um comando que falhou com `--json`.
+pub fn error_json(err: &CloudTrayError) -> Value {
+	serde_json::json!({ "error": err })
+}
+
+/// Valor de uma chave da configuração, com pontos para chaves aninhadas (ex.: network.proxy_url).
+pub fn config_value<'a>(value: &'a Value, key: &str) -> Option<&'a Value> {
+	key.split('.').try_fold(value, |current, part| current.get(part))
+}
+
+/// Altera uma chave da configuração; `raw` é lido como JSON e, se não for JSON válido, como texto.
+pub fn set_config_key(config: &AppConfig, key: &str, raw: &str) -> Result<AppConfig, CloudTrayError> {
+	// `save_config` mantém as pastas salvas, então a alteração seria descartada sem aviso
+	if key == "drive_folders" || key.starts_with("drive_folders.") {
+		return Err(CloudTrayError::invalid_input(
+			"drive_folders é mantido pelo app; use `cloudtray-cli folder set` para trocar a pasta de destino",
+		));
+	}
+
+	let mut value = serde_json::to_value(config)
+		.map_err(|e| CloudTrayError::internal(format!("Erro ao serializar configuração: {}", e)))?;
+
+	let new_value = serde_json::from_str(raw).unwrap_or_else(|_| Value::String(raw.to_string()));
+	set_config_value(&mut value, key, new_value)?;
+
+	let updated: AppConfig = serde_json::from_value(value)
+		.map_err(|e| CloudTrayError::invalid_input(format!("Valor inválido para {}: {}", key, e)))?;
+
+	// Campos desconhecidos são descartados na desserialização; sem esta checagem um erro de digitação seria salvo sem efeito
+	let saved = serde_json::to_value(&updated)
+		.map_err(|e| CloudTrayError::internal(format!("Erro ao serializar configuração: {}", e)))?;
+	if config_value(&saved, key).is_none() {
+		return Err(CloudTrayError::invalid_input(format!("Chave de configuração desconhecida: {}", key)));
+	}
+
+	Ok(updated)
+}
+
+fn set_config_value(value: &mut Value, key: &str, new_value: Value) -> Result<(), CloudTrayError> {
+	let mut current = value;
+	let mut parts = key.split('.').peekable();
+
+	while let Some(part) = parts.next() {
+		if current.is_null() {
+			*current = Value::Object(Default::default());
+		}
+		let object = current
+			.as_object_mut()
+			.ok_or_else(|| CloudTrayError::invalid_input(format!("Chave de configuração inválida: {}", key)))?;
+
+		if parts.peek().is_none() {
+			object.insert(part.to_string(), new_value);
+			return Ok(());
+		}
+		current = object.entry(part.to_string()).or_insert(Value::Null);
+	}
+
+	Err(CloudTrayError::invalid_input("Chave de configuração vazia"))
+}
@@ -0,0 +1,22 @@
+use cloudtray_core::accounts::{self, AccountRegistry};
+use cloudtray_core::error::CloudTrayError;
+use cloudtray_core::GoogleCredentials;
+use tauri::{command, State};
+
+#[command]
+pub async fn list_accounts() -> Result<AccountRegistry, CloudTrayError> {
+	accounts::list_accounts().await
+}
+
+#[command]
+pub async fn switch_account(account_id: String) -> Result<AccountRegistry, CloudTrayError> {
+	accounts::switch_account(account_id).await
+}
+
+#[command]
+pub async fn remove_account(
+	account_id: String,
+	credentials: State<'_, GoogleCredentials>,
+) -> Result<AccountRegistry, CloudTrayError> {
+	accounts::remove_account_data(&account_id, &credentials).await
+}
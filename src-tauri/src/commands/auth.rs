@@ -0,0 +1,99 @@
+use cloudtray_core::accounts::Account;
+use cloudtray_core::auth::{self, DeviceAuthorization, LogoutReport};
+use cloudtray_core::drive::GoogleTokens;
+use cloudtray_core::error::CloudTrayError;
+use cloudtray_core::http_client::HttpClient;
+use cloudtray_core::GoogleCredentials;
+use tauri::{command, Manager, State};
+use tauri_plugin_oauth::start;
+
+#[command]
+pub fn set_google_credentials(
+	credentials: State<GoogleCredentials>,
+	client_id: String,
+	client_secret: Option<String>,
+) {
+	*credentials.client_id.lock().unwrap() = client_id;
+	*credentials.client_secret.lock().unwrap() = client_secret.unwrap_or_default();
+}
+
+#[command]
+pub async fn start_oauth_server(window: tauri::Window) -> Result<u16, CloudTrayError> {
+	start(move |url| {
+		let _ = window.show();
+		let _ = window.set_focus();
+		std::thread::sleep(std::time::Duration::from_millis(100));
+
+		let credentials = window.state::<GoogleCredentials>();
+		match auth::verify_callback_state(&credentials, &url) {
+			Ok(()) => {
+				let _ = window.emit("oauth_callback", url.clone());
+			}
+			Err(err) => {
+				let _ = window.emit("oauth_callback_error", err.message());
+			}
+		}
+	})
+	.map_err(|err| CloudTrayError::internal(format!("Erro ao iniciar servidor OAuth: {}", err)))
+}
+
+#[command]
+pub async fn build_authorization_url(
+	redirect_uri: String,
+	credentials: State<'_, GoogleCredentials>,
+) -> Result<String, CloudTrayError> {
+	auth::build_authorization_url(redirect_uri, &credentials).await
+}
+
+#[command]
+pub async fn exchange_auth_code(
+	code: String,
+	credentials: State<'_, GoogleCredentials>,
+	http: State<'_, HttpClient>,
+) -> Result<GoogleTokens, CloudTrayError> {
+	auth::exchange_auth_code(code, &credentials, &http).await
+}
+
+#[command]
+pub async fn get_tokens(
+	account_id: Option<String>,
+	credentials: State<'_, GoogleCredentials>,
+	http: State<'_, HttpClient>,
+) -> Result<GoogleTokens, CloudTrayError> {
+	auth::get_tokens(account_id, &credentials, &http).await
+}
+
+#[command]
+pub async fn save_tokens(
+	tokens: GoogleTokens,
+	credentials: State<'_, GoogleCredentials>,
+	http: State<'_, HttpClient>,
+) -> Result<Account, CloudTrayError> {
+	auth::save_tokens(tokens, &credentials, &http).await
+}
+
+#[command]
+pub async fn logout(
+	account_id: Option<String>,
+	trash_folder: Option<bool>,
+	credentials: State<'_, GoogleCredentials>,
+	http: State<'_, HttpClient>,
+) -> Result<LogoutReport, CloudTrayError> {
+	auth::logout(account_id, trash_folder.unwrap_or(false), &credentials, &http).await
+}
+
+#[command]
+pub async fn start_device_authorization(
+	credentials: State<'_, GoogleCredentials>,
+	http: State<'_, HttpClient>,
+) -> Result<DeviceAuthorization, CloudTrayError> {
+	auth::start_device_authorization(&credentials, &http).await
+}
+
+#[command]
+pub async fn complete_device_authorization(
+	credentials: State<'_, GoogleCredentials>,
+	http: State<'_, HttpClient>,
+) -> Result<Account, CloudTrayError> {
+	auth::complete_device_authorization(&credentials, &http).await
+}
@@ -0,0 +1,17 @@
+//! Comandos expostos ao frontend. Só extraem o estado do Tauri e repassam ao `cloudtray_core`.
+
+pub mod accounts;
+pub mod auth;
+pub mod queue;
+pub mod settings;
+pub mod storage;
+
+use cloudtray_core::progress::ProgressCallback;
+use tauri::Runtime;
+
+/// Repassa o progresso dos uploads à janela como o evento `upload-progress`.
+pub fn window_progress<R: Runtime>(window: tauri::Window<R>) -> ProgressCallback {
+	Box::new(move |progress| {
+		let _ = window.emit("upload-progress", progress);
+	})
+}
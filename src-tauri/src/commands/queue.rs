@@ -0,0 +1,73 @@
+use tauri::{command, Manager, State};
+
+use cloudtray_core::accounts::resolve_account_id;
+use cloudtray_core::config::load_or_create_config;
+use cloudtray_core::error::CloudTrayError;
+use cloudtray_core::http_client::HttpClient;
+use cloudtray_core::queue::{UploadJob, UploadQueue};
+use cloudtray_core::storage::upload_file_path;
+use cloudtray_core::GoogleCredentials;
+
+use super::window_progress;
+
+/// Fila do app: envia pelos estados gerenciados pelo Tauri e avisa a janela com o evento `upload-job`.
+pub fn upload_queue(window: tauri::Window) -> UploadQueue {
+	let worker_window = window.clone();
+
+	UploadQueue::new(
+		Box::new(move |job: UploadJob| {
+			let window = worker_window.clone();
+			Box::pin(async move {
+				let credentials = window.state::<GoogleCredentials>();
+				let http = window.state::<HttpClient>();
+				let on_progress = window_progress(window.clone());
+				upload_file_path(job.file_path, job.folder_id, Some(job.account_id), &credentials, &http, on_progress).await
+			})
+		}),
+		Box::new(move |job: UploadJob| {
+			let _ = window.emit("upload-job", job);
+		}),
+	)
+}
+
+#[command]
+pub async fn enqueue_uploads(
+	file_paths: Vec<String>,
+	folder_id: String,
+	account_id: Option<String>,
+	queue: State<'_, UploadQueue>,
+	credentials: State<'_, GoogleCredentials>,
+	http: State<'_, HttpClient>,
+) -> Result<Vec<UploadJob>, CloudTrayError> {
+	// A conta é fixada no momento do enfileiramento, mesmo que o usuário troque de conta depois
+	let account_id = resolve_account_id(account_id, &credentials, &http.client()).await?;
+	let config = load_or_create_config().await?;
+
+	queue.enqueue(file_paths, folder_id, account_id, config.max_parallel_uploads)
+}
+
+#[command]
+pub async fn list_uploads(queue: State<'_, UploadQueue>) -> Result<Vec<UploadJob>, CloudTrayError> {
+	Ok(queue.list())
+}
+
+#[command]
+pub async fn cancel_upload(id: u64, queue: State<'_, UploadQueue>) -> Result<(), CloudTrayError> {
+	queue.cancel(id)
+}
+
+#[command]
+pub async fn pause_upload(id: u64, queue: State<'_, UploadQueue>) -> Result<(), CloudTrayError> {
+	queue.pause(id)
+}
+
+#[command]
+pub async fn resume_upload(id: u64, queue: State<'_, UploadQueue>) -> Result<(), CloudTrayError> {
+	queue.resume(id)
+}
+
+#[command]
+pub async fn clear_finished_uploads(queue: State<'_, UploadQueue>) -> Result<(), CloudTrayError> {
+	queue.clear_finished();
+	Ok(())
+}
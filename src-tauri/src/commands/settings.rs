@@ -0,0 +1,41 @@
+use cloudtray_core::config::{self, AppConfig};
+use cloudtray_core::diagnostics::{self, Diagnostics};
+use cloudtray_core::error::CloudTrayError;
+use cloudtray_core::http_client::{self, HttpClient};
+use cloudtray_core::{s3, webdav, GoogleCredentials};
+use tauri::{command, State};
+
+#[command]
+pub async fn load_or_create_config() -> Result<AppConfig, CloudTrayError> {
+	config::load_or_create_config().await
+}
+
+#[command]
+pub async fn save_config(
+	config: AppConfig,
+	credentials: State<'_, GoogleCredentials>,
+	http: State<'_, HttpClient>,
+) -> Result<(), CloudTrayError> {
+	config::save_config(config, &credentials, &http).await
+}
+
+#[command]
+pub async fn set_s3_secret(secret_access_key: String) -> Result<(), CloudTrayError> {
+	s3::set_s3_secret(secret_access_key).await
+}
+
+#[command]
+pub async fn set_webdav_password(password: String) -> Result<(), CloudTrayError> {
+	webdav::set_webdav_password(password).await
+}
+
+#[command]
+pub async fn set_proxy_password(password: String, http: State<'_, HttpClient>) -> Result<(), CloudTrayError> {
+	http_client::set_proxy_password(password, &http).await
+}
+
+/// Retentativas e avisos recentes, já que o app não tem console para mostrá-los.
+#[command]
+pub fn get_diagnostics() -> Diagnostics {
+	diagnostics::diagnostics()
+}
@@ -0,0 +1,80 @@
+use cloudtray_core::drive::{DriveFile, DriveFolder};
+use cloudtray_core::error::CloudTrayError;
+use cloudtray_core::http_client::HttpClient;
+use cloudtray_core::storage;
+use cloudtray_core::GoogleCredentials;
+use tauri::{command, State};
+
+use super::window_progress;
+
+#[command]
+pub async fn get_or_create_app_folder(
+	account_id: Option<String>,
+	credentials: State<'_, GoogleCredentials>,
+	http: State<'_, HttpClient>,
+) -> Result<DriveFolder, CloudTrayError> {
+	storage::get_or_create_app_folder(account_id, &credentials, &http).await
+}
+
+#[command]
+pub async fn list_drive_folders(
+	account_id: Option<String>,
+	credentials: State<'_, GoogleCredentials>,
+	http: State<'_, HttpClient>,
+) -> Result<Vec<DriveFolder>, CloudTrayError> {
+	storage::list_drive_folders(account_id, &credentials, &http).await
+}
+
+#[command]
+pub async fn select_drive_folder(
+	folder: String,
+	account_id: Option<String>,
+	credentials: State<'_, GoogleCredentials>,
+	http: State<'_, HttpClient>,
+) -> Result<DriveFolder, CloudTrayError> {
+	storage::select_drive_folder(folder, account_id, &credentials, &http).await
+}
+
+#[command]
+pub async fn upload_file_path(
+	window: tauri::Window,
+	file_path: String,
+	folder_id: String,
+	account_id: Option<String>,
+	credentials: State<'_, GoogleCredentials>,
+	http: State<'_, HttpClient>,
+) -> Result<DriveFile, CloudTrayError> {
+	storage::upload_file_path(file_path, folder_id, account_id, &credentials, &http, window_progress(window)).await
+}
+
+#[command]
+pub async fn upload_file(
+	window: tauri::Window,
+	file_content: Vec<u8>,
+	file_name: String,
+	folder_id: String,
+	account_id: Option<String>,
+	credentials: State<'_, GoogleCredentials>,
+	http: State<'_, HttpClient>,
+) -> Result<DriveFile, CloudTrayError> {
+	storage::upload_file(file_content, file_name, folder_id, account_id, &credentials, &http, window_progress(window)).await
+}
+
+#[command]
+pub async fn list_recent_files(
+	account_id: Option<String>,
+	credentials: State<'_, GoogleCredentials>,
+	http: State<'_, HttpClient>,
+) -> Result<Vec<DriveFile>, CloudTrayError> {
+	storage::list_recent_files(account_id, &credentials, &http).await
+}
+
+#[command]
+pub async fn delete_file(
+	file_id: String,
+	account_id: Option<String>,
+	credentials: State<'_, GoogleCredentials>,
+	http: State<'_, HttpClient>,
+) -> Result<(), CloudTrayError> {
+	storage::delete_file(file_id, account_id, &credentials, &http).await
+}
@@ -1,63 +1,205 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
 use serde::{Deserialize, Serialize};
-use tauri::command;
+
+use crate::endpoints::GoogleEndpoints;
+use crate::error::CloudTrayError;
+use crate::http_client::{HttpClient, NetworkSettings};
+use crate::s3::S3Settings;
+use crate::storage::ProviderKind;
+use crate::webdav::WebDavSettings;
+use crate::GoogleCredentials;
+
+pub const CONFIG_DIR_ENV: &str = "CLOUDTRAY_CONFIG_DIR";
+
+/// `identifier` do tauri.conf.json.
+pub const APP_IDENTIFIER: &str = "com.cloudtray.app";
+
+// Arquivos da versão 1.0, que ficavam em `<config>/config` (o `app_config_dir` do Tauri com identificador vazio)
+const LEGACY_FILES: [&str; 2] = ["app_config.json", "google_tokens.json"];
+
+static LEGACY_MIGRATION: tokio::sync::OnceCell<()> = tokio::sync::OnceCell::const_new();
+
+// O app altera a configuração sozinho (pasta do Drive), então leitura e escrita não podem se intercalar
+static CONFIG_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct AppConfig {
 	pub retention_hours: i64,
+	#[serde(default = "default_max_parallel_uploads")]
+	pub max_parallel_uploads: usize,
+	#[serde(default)]
+	pub provider: ProviderKind,
+	#[serde(default)]
+	pub s3: Option<S3Settings>,
+	#[serde(default)]
+	pub webdav: Option<WebDavSettings>,
+	#[serde(default)]
+	pub network: NetworkSettings,
+	#[serde(default)]
+	pub google_endpoints: Option<GoogleEndpoints>,
+	/// Pasta de destino no Google Drive de cada conta (e-mail → id da pasta), mantida pelo app
+	#[serde(default)]
+	pub drive_folders: HashMap<String, String>,
+}
+
+fn default_max_parallel_uploads() -> usize {
+	2
 }
 
 impl Default for AppConfig {
 	fn default() -> Self {
 		Self {
 			retention_hours: 24,
+			max_parallel_uploads: default_max_parallel_uploads(),
+			provider: ProviderKind::default(),
+			s3: None,
+			webdav: None,
+			network: NetworkSettings::default(),
+			google_endpoints: None,
+			drive_folders: HashMap::new(),
+		}
+	}
+}
+
+/// Perfil isolado definido por `CLOUDTRAY_CONFIG_DIR` (testes, instalações portáteis).
+pub fn isolated_config_dir() -> Option<PathBuf> {
+	std::env::var_os(CONFIG_DIR_ENV)
+		.filter(|dir| !dir.is_empty())
+		.map(PathBuf::from)
+}
+
+/// Pasta com app_config.json, contas, tokens e sessões de upload.
+pub fn config_dir() -> Result<PathBuf, CloudTrayError> {
+	if let Some(dir) = isolated_config_dir() {
+		return Ok(dir);
+	}
+
+	let app_config_dir = dirs::config_dir()
+		.ok_or_else(|| CloudTrayError::config("Não foi possível encontrar o diretório de configuração"))?
+		.join(APP_IDENTIFIER);
+	Ok(app_config_dir.join("config"))
+}
+
+/// Traz a configuração e os tokens da pasta antiga na primeira chamada do processo.
+pub(crate) async fn migrate_legacy_config_dir() -> Result<(), CloudTrayError> {
+	if isolated_config_dir().is_some() {
+		return Ok(());
+	}
+
+	LEGACY_MIGRATION
+		.get_or_try_init(|| async {
+			match dirs::config_dir() {
+				Some(dir) => migrate_legacy_files(&dir.join("config"), &config_dir()?).await,
+				None => Ok(()),
+			}
+		})
+		.await?;
+	Ok(())
+}
+
+/// Move os arquivos da versão 1.0 para a pasta atual. Os tokens seguem para a migração de conta, que os
+/// cifra e apaga; se a pasta atual já tiver o arquivo, a cópia antiga é só removida.
+pub(crate) async fn migrate_legacy_files(legacy_dir: &Path, config_dir: &Path) -> Result<(), CloudTrayError> {
+	for name in LEGACY_FILES {
+		let legacy_file = legacy_dir.join(name);
+		if !legacy_file.exists() {
+			continue;
+		}
+
+		let target = config_dir.join(name);
+		if !target.exists() {
+			tokio::fs::create_dir_all(config_dir)
+				.await
+				.map_err(|e| CloudTrayError::io(format!("Erro ao criar diretório de configuração: {}", e)))?;
+			if tokio::fs::rename(&legacy_file, &target).await.is_ok() {
+				continue;
+			}
+			tokio::fs::copy(&legacy_file, &target)
+				.await
+				.map_err(|e| CloudTrayError::io(format!("Erro ao migrar {}: {}", name, e)))?;
 		}
+
+		tokio::fs::remove_file(&legacy_file)
+			.await
+			.map_err(|e| CloudTrayError::io(format!("Erro ao remover {} antigo: {}", name, e)))?;
 	}
+
+	Ok(())
 }
 
-#[command]
-pub async fn load_or_create_config() -> Result<AppConfig, String> {
-  let config_dir = tauri::api::path::app_config_dir(&tauri::Config::default())
-	  .ok_or("Não foi possível encontrar o diretório de configuração")?;
-  let config_path = config_dir.join("config");
+pub async fn load_or_create_config() -> Result<AppConfig, CloudTrayError> {
+  migrate_legacy_config_dir().await?;
+  let config_path = config_dir()?;
   let config_file = config_path.join("app_config.json");
   
   if !config_path.exists() {
 	  tokio::fs::create_dir_all(&config_path)
 		  .await
-		  .map_err(|e| format!("Erro ao criar diretório de configuração: {}", e))?;
+		  .map_err(|e| CloudTrayError::io(format!("Erro ao criar diretório de configuração: {}", e)))?;
   }
   
   if config_file.exists() {
 	  let config_str = tokio::fs::read_to_string(&config_file)
 		  .await
-		  .map_err(|e| format!("Erro ao ler arquivo de configuração: {}", e))?;
+		  .map_err(|e| CloudTrayError::io(format!("Erro ao ler arquivo de configuração: {}", e)))?;
 		  
 	  serde_json::from_str(&config_str)
-		  .map_err(|e| format!("Erro ao parsear configuração: {}", e))
+		  .map_err(|e| CloudTrayError::config(format!("Erro ao parsear configuração: {}", e)))
   } else {
 	  let default_config = AppConfig::default();
 	  let config_json = serde_json::to_string_pretty(&default_config)
-		  .map_err(|e| format!("Erro ao serializar configuração: {}", e))?;
+		  .map_err(|e| CloudTrayError::internal(format!("Erro ao serializar configuração: {}", e)))?;
 		  
 	  tokio::fs::write(&config_file, config_json)
 		  .await
-		  .map_err(|e| format!("Erro ao salvar configuração: {}", e))?;
+		  .map_err(|e| CloudTrayError::io(format!("Erro ao salvar configuração: {}", e)))?;
 		  
 	  Ok(default_config)
   }
 }
 
-#[command]
-pub async fn save_config(config: AppConfig) -> Result<(), String> {
-  let config_dir = tauri::api::path::app_config_dir(&tauri::Config::default())
-	  .ok_or("Não foi possível encontrar o diretório de configuração")?;
-  let config_path = config_dir.join("config");
-  let config_file = config_path.join("app_config.json");
+pub async fn save_config(
+  mut config: AppConfig,
+  credentials: &GoogleCredentials,
+  http: &HttpClient,
+) -> Result<(), CloudTrayError> {
+  // Valida proxy e certificados antes de salvar, para não deixar uma configuração quebrada em disco
+  http.configure(&config.network).await?;
+  *credentials.endpoints.lock().unwrap() = GoogleEndpoints::from_config(&config);
+
+  let _guard = CONFIG_LOCK.lock().await;
+
+  // As pastas do Drive são mantidas pelo app (`set_drive_folder`); uma cópia antiga vinda da tela de configurações não as sobrescreve
+  config.drive_folders = load_or_create_config().await?.drive_folders;
+
+  write_config(&config).await
+}
+
+/// Guarda (ou esquece, com `None`) a pasta de destino da conta no Google Drive.
+pub async fn set_drive_folder(account_id: &str, folder_id: Option<String>) -> Result<(), CloudTrayError> {
+  let _guard = CONFIG_LOCK.lock().await;
+  let mut config = load_or_create_config().await?;
+
+  let changed = match folder_id {
+	  Some(folder_id) => config.drive_folders.insert(account_id.to_string(), folder_id.clone()) != Some(folder_id),
+	  None => config.drive_folders.remove(account_id).is_some(),
+  };
+
+  if changed {
+	  write_config(&config).await?;
+  }
+  Ok(())
+}
+
+async fn write_config(config: &AppConfig) -> Result<(), CloudTrayError> {
+  let config_file = config_dir()?.join("app_config.json");
   
-  let config_json = serde_json::to_string_pretty(&config)
-	  .map_err(|e| format!("Erro ao serializar configuração: {}", e))?;
+  let config_json = serde_json::to_string_pretty(config)
+	  .map_err(|e| CloudTrayError::internal(format!("Erro ao serializar configuração: {}", e)))?;
 	  
   tokio::fs::write(&config_file, config_json)
 	  .await
-	  .map_err(|e| format!("Erro ao salvar configuração: {}", e))
+	  .map_err(|e| CloudTrayError::io(format!("Erro ao salvar configuração: {}", e)))
 }
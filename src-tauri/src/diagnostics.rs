@@ -0,0 +1,60 @@
+//! Avisos de operações que não interrompem o fluxo (retentativas, limpezas que falharam).
+//! O app não tem console no Windows, então os avisos ficam guardados em memória e são repassados
+//! a quem estiver ouvindo: a janela do tray (evento `app-warning`) ou o stderr da CLI.
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use chrono::Utc;
+use serde::Serialize;
+
+const MAX_WARNINGS: usize = 100;
+
+#[derive(Debug, Serialize, Clone)]
+pub struct Warning {
+	pub timestamp: i64,
+	pub message: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct Diagnostics {
+	/// Retentativas de requisições desde que o app foi aberto
+	pub retry_count: u64,
+	pub warnings: Vec<Warning>,
+}
+
+pub type WarningCallback = Box<dyn Fn(Warning) + Send + Sync>;
+
+static WARNINGS: Mutex<VecDeque<Warning>> = Mutex::new(VecDeque::new());
+static ON_WARNING: Mutex<Option<WarningCallback>> = Mutex::new(None);
+
+/// Define quem recebe os próximos avisos; os anteriores continuam disponíveis em `diagnostics()`.
+pub fn set_warning_listener(on_warning: Option<WarningCallback>) {
+	*ON_WARNING.lock().unwrap() = on_warning;
+}
+
+pub fn warn(message: impl Into<String>) {
+	let warning = Warning {
+		timestamp: Utc::now().timestamp(),
+		message: message.into(),
+	};
+
+	{
+		let mut warnings = WARNINGS.lock().unwrap();
+		if warnings.len() == MAX_WARNINGS {
+			warnings.pop_front();
+		}
+		warnings.push_back(warning.clone());
+	}
+
+	if let Some(on_warning) = ON_WARNING.lock().unwrap().as_ref() {
+		on_warning(warning);
+	}
+}
+
+pub fn diagnostics() -> Diagnostics {
+	Diagnostics {
+		retry_count: crate::retry::retry_count(),
+		warnings: WARNINGS.lock().unwrap().iter().cloned().collect(),
+	}
+}
@@ -1,15 +1,29 @@
-use chrono::Utc;
-use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_TYPE};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use futures_util::future::BoxFuture;
+use futures_util::stream::{self, StreamExt};
+use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_LENGTH, CONTENT_TYPE};
 use serde::{Deserialize, Serialize};
 
-use crate::config::load_or_create_config;
+use crate::accounts::resolve_account_id;
 use crate::auth::get_tokens;
-
-use tauri::command;
-use tauri::State;
+use crate::config::{load_or_create_config, set_drive_folder};
+use crate::endpoints::GoogleEndpoints;
+use crate::error::CloudTrayError;
+use crate::http_client::HttpClient;
+use crate::progress::{counting_stream, ByteStream, ProgressTracker};
+use crate::retry::send_with_retry;
+use crate::resumable::{content_session_key, path_session_key, upload_resumable, RESUMABLE_THRESHOLD};
+use crate::storage::{StorageProvider, UploadRequest, UploadSource};
 use crate::GoogleCredentials;
 
 const APP_FOLDER_NAME: &str = "CloudTray";
+const FOLDER_MIME_TYPE: &str = "application/vnd.google-apps.folder";
+// Marca as pastas criadas pelo app, as únicas que o logout pode mandar para a lixeira
+const APP_FOLDER_PROPERTY: &str = "cloudtrayAppFolder";
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct GoogleTokens {
@@ -17,6 +31,11 @@ pub struct GoogleTokens {
 	pub refresh_token: String,
 	pub expires_in: i32,
 	pub token_type: String,
+	#[serde(default)]
+	pub expires_at: i64,
+	/// Escopos concedidos, como devolvidos pelo token endpoint
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub scope: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -33,330 +52,630 @@ pub struct DriveFile {
 	pub web_view_link: String,
 }
 
-#[command]
-pub async fn get_or_create_app_folder(credentials: State<'_, GoogleCredentials>) -> Result<DriveFolder, String> {
-	let tokens = get_tokens(credentials).await?;
-	let client = reqwest::Client::new();
-	
-	let mut headers = HeaderMap::new();
-	headers.insert(AUTHORIZATION, HeaderValue::from_str(&format!("Bearer {}", tokens.access_token)).unwrap());
-	
-	let query = format!("name = '{}' and mimeType = 'application/vnd.google-apps.folder' and trashed = false", APP_FOLDER_NAME);
-	
-	let response = client
-		.get("https://www.googleapis.com/drive/v3/files")
-		.headers(headers.clone())
-		.query(&[
-			("q", &query),
-			("fields", &"files(id, name)".to_string()),
-		])
-		.send()
-		.await
-		.map_err(|e| {
-			e.to_string()
-		})?;
+#[derive(Debug, Deserialize)]
+struct FileIdOnly {
+	id: String,
+}
+
+// Envelope de erro padrão das APIs do Google: {"error": {"code", "message", "errors": [{"reason"}]}}
+#[derive(Debug, Deserialize)]
+struct GoogleErrorResponse {
+	error: GoogleErrorBody,
+}
 
-	let response_text = response.text().await.map_err(|e| e.to_string())?;
+#[derive(Debug, Deserialize)]
+struct GoogleErrorBody {
+	code: Option<u16>,
+	message: Option<String>,
+	#[serde(default)]
+	errors: Vec<GoogleErrorDetail>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GoogleErrorDetail {
+	reason: Option<String>,
+}
 
-	#[derive(Debug, Deserialize)]
-	struct FileList {
-		files: Vec<DriveFolder>,
+pub struct GoogleDriveProvider<'a> {
+	account_id: Option<String>,
+	credentials: &'a GoogleCredentials,
+	http: &'a HttpClient,
+}
+
+impl<'a> GoogleDriveProvider<'a> {
+	pub fn new(account_id: Option<String>, credentials: &'a GoogleCredentials, http: &'a HttpClient) -> Self {
+		Self { account_id, credentials, http }
 	}
 
-	let file_list: FileList = serde_json::from_str(&response_text)
-		.map_err(|e| format!("Erro ao parsear lista de arquivos: {}. Resposta: {}", e, response_text))?;
+	fn client(&self) -> reqwest::Client {
+		self.http.client()
+	}
 
-	if let Some(folder) = file_list.files.first() {
-		return Ok(DriveFolder {
-			id: folder.id.clone(),
-			name: folder.name.clone(),
-		});
+	fn endpoints(&self) -> GoogleEndpoints {
+		self.credentials.endpoints.lock().unwrap().clone()
+	}
+
+	async fn access_token(&self) -> Result<String, CloudTrayError> {
+		let tokens = get_tokens(self.account_id.clone(), self.credentials, self.http).await?;
+		Ok(tokens.access_token)
 	}
 
-	headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+	async fn auth_headers(&self) -> Result<HeaderMap, CloudTrayError> {
+		let access_token = self.access_token().await?;
 
-	let folder_metadata = serde_json::json!({
-		"name": APP_FOLDER_NAME,
-		"mimeType": "application/vnd.google-apps.folder"
-	});
+		let mut headers = HeaderMap::new();
+		headers.insert(AUTHORIZATION, HeaderValue::from_str(&format!("Bearer {}", access_token)).unwrap());
+		Ok(headers)
+	}
 
-	let create_response = client
-		.post("https://www.googleapis.com/drive/v3/files")
-		.headers(headers.clone())
-		.json(&folder_metadata)
-		.send()
-		.await
-		.map_err(|e| {
-			e.to_string()
+	async fn account_key(&self) -> Result<String, CloudTrayError> {
+		resolve_account_id(self.account_id.clone(), self.credentials, &self.client()).await
+	}
+
+	async fn tracked_folder_id(&self, account: &str) -> Result<Option<String>, CloudTrayError> {
+		Ok(load_or_create_config().await?.drive_folders.remove(account))
+	}
+
+	async fn track_folder(&self, account: &str, folder: Option<&DriveFolder>) -> Result<(), CloudTrayError> {
+		set_drive_folder(account, folder.map(|folder| folder.id.clone())).await
+	}
+
+	/// Busca a pasta pelo id; `None` se ela foi apagada, está na lixeira ou não é uma pasta.
+	async fn get_folder(&self, folder_id: &str) -> Result<Option<DriveFolder>, CloudTrayError> {
+		let headers = self.auth_headers().await?;
+
+		let request = self
+			.client()
+			.get(self.endpoints().file_url(folder_id))
+			.headers(headers)
+			.query(&[("fields", "id,name,mimeType,trashed")]);
+		let response = send_with_retry(request, "buscar pasta").await?;
+
+		if response.status() == reqwest::StatusCode::NOT_FOUND {
+			return Ok(None);
+		}
+
+		#[derive(Debug, Deserialize)]
+		struct FolderStatus {
+			id: String,
+			name: String,
+			#[serde(rename = "mimeType")]
+			mime_type: String,
+			#[serde(default)]
+			trashed: bool,
+		}
+
+		let folder: FolderStatus = parse_response(response, "buscar pasta").await?;
+
+		let usable = !folder.trashed && folder.mime_type == FOLDER_MIME_TYPE;
+		Ok(usable.then_some(DriveFolder { id: folder.id, name: folder.name }))
+	}
+
+	/// Pasta de destino da conta: primeiro pelo id salvo na configuração e, na falta dele, pelo nome.
+	/// Se a pasta salva foi apagada ou está na lixeira, ela é esquecida e outra é usada (ou criada).
+	/// Com o escopo drive.file a busca só enxerga pastas criadas pelo próprio app; versões antigas
+	/// não marcavam a pasta, então ela é marcada ao ser encontrada pelo nome.
+	async fn find_app_folder(&self) -> Result<Option<DriveFolder>, CloudTrayError> {
+		let account = self.account_key().await?;
+
+		if let Some(folder_id) = self.tracked_folder_id(&account).await? {
+			if let Some(folder) = self.get_folder(&folder_id).await? {
+				return Ok(Some(folder));
+			}
+			self.track_folder(&account, None).await?;
+		}
+
+		let folder = self.search_app_folder().await?;
+		if let Some(folder) = &folder {
+			self.tag_app_folder(&folder.id).await?;
+		}
+		self.track_folder(&account, folder.as_ref()).await?;
+
+		Ok(folder)
+	}
+
+	/// Pastas do CloudTray que podem receber os uploads. Com o escopo drive.file o app só enxerga
+	/// as pastas que ele mesmo criou, então a troca é sempre entre pastas do CloudTray.
+	pub async fn list_folders(&self) -> Result<Vec<DriveFolder>, CloudTrayError> {
+		let query = format!("mimeType = '{}' and trashed = false", FOLDER_MIME_TYPE);
+
+		#[derive(Debug, Deserialize)]
+		struct FolderList {
+			files: Vec<DriveFolder>,
+			#[serde(rename = "nextPageToken")]
+			next_page_token: Option<String>,
+		}
+
+		let mut folders = Vec::new();
+		let mut page_token: Option<String> = None;
+		loop {
+			let headers = self.auth_headers().await?;
+
+			let mut request = self
+				.client()
+				.get(self.endpoints().files_url())
+				.headers(headers)
+				.query(&[
+					("q", query.as_str()),
+					("orderBy", "name"),
+					("fields", "nextPageToken, files(id, name)"),
+					("pageSize", "100"),
+				]);
+			if let Some(token) = &page_token {
+				request = request.query(&[("pageToken", token)]);
+			}
+			let response = send_with_retry(request, "listar pastas").await?;
+
+			let folder_list: FolderList = parse_response(response, "listar pastas").await?;
+			folders.extend(folder_list.files);
+
+			match folder_list.next_page_token {
+				Some(token) => page_token = Some(token),
+				None => return Ok(folders),
+			}
+		}
+	}
+
+	/// Usa uma pasta existente (id ou link do Drive) como destino dos uploads da conta.
+	pub async fn select_folder(&self, folder: &str) -> Result<DriveFolder, CloudTrayError> {
+		let folder_id = folder_id_from_input(folder)
+			.ok_or_else(|| CloudTrayError::invalid_input(format!("Pasta inválida: {}", folder)))?;
+
+		let folder = self.get_folder(&folder_id).await?.ok_or_else(|| {
+			CloudTrayError::not_found(format!(
+				"Pasta {} não encontrada entre as pastas do CloudTray",
+				folder_id
+			))
 		})?;
 
-	let create_text = create_response.text().await.map_err(|e| e.to_string())?;
+		self.track_folder(&self.account_key().await?, Some(&folder)).await?;
 
-	let folder: DriveFolder = serde_json::from_str(&create_text)
-		.map_err(|e| format!("Erro ao parsear pasta criada: {}. Resposta: {}", e, create_text))?;
+		Ok(folder)
+	}
 
-	let permission_body = serde_json::json!({
-		"role": "reader",
-		"type": "anyone"
-	});
+	async fn search_app_folder(&self) -> Result<Option<DriveFolder>, CloudTrayError> {
+		let headers = self.auth_headers().await?;
 
-	let _permission_response = client
-		.post(&format!("https://www.googleapis.com/drive/v3/files/{}/permissions", folder.id))
-		.headers(headers)
-		.json(&permission_body)
-		.send()
-		.await
-		.map_err(|e| format!("Erro ao definir permissões: {}", e))?;
+		let query = format!("name = '{}' and mimeType = '{}' and trashed = false", APP_FOLDER_NAME, FOLDER_MIME_TYPE);
 
-	Ok(folder)
-}
+		let request = self
+			.client()
+			.get(self.endpoints().files_url())
+			.headers(headers)
+			.query(&[
+				("q", &query),
+				("fields", &"files(id, name)".to_string()),
+			]);
+		let response = send_with_retry(request, "buscar pasta").await?;
+
+		#[derive(Debug, Deserialize)]
+		struct FileList {
+			files: Vec<DriveFolder>,
+		}
 
-#[command]
-pub async fn upload_file_path(
-	window: tauri::Window,
-	file_path: String,
-	folder_id: String,
-	credentials: State<'_, GoogleCredentials>,
-) -> Result<DriveFile, String> {
-	let _ = delete_old_files(&folder_id, credentials.clone()).await;
+		let file_list: FileList = parse_response(response, "buscar pasta").await?;
 
-	let file_content = tokio::fs::read(&file_path)
-		.await
-		.map_err(|e| format!("Erro ao ler arquivo: {}", e))?;
-	
-	let file_name = std::path::Path::new(&file_path)
-		.file_name()
-		.and_then(|name| name.to_str())
-		.ok_or("Nome do arquivo inválido")?
-		.to_string();
-
-	upload_file(window, file_content, file_name, folder_id, credentials).await
-}
+		Ok(file_list.files.into_iter().next())
+	}
 
-#[command]
-pub async fn upload_file(
-	window: tauri::Window,
-	file_content: Vec<u8>,
-	file_name: String,
-	folder_id: String,
-	credentials: State<'_, GoogleCredentials>,
-) -> Result<DriveFile, String> {
-	let _ = delete_old_files(&folder_id, credentials.clone()).await;
+	/// Marca a pasta em `appProperties` como criada pelo CloudTray.
+	async fn tag_app_folder(&self, folder_id: &str) -> Result<(), CloudTrayError> {
+		let headers = self.auth_headers().await?;
 
-	let tokens = get_tokens(credentials).await?;
-	let client = reqwest::Client::new();
+		let request = self
+			.client()
+			.patch(self.endpoints().file_url(folder_id))
+			.headers(headers)
+			.json(&serde_json::json!({ "appProperties": { APP_FOLDER_PROPERTY: "true" } }));
+		let response = send_with_retry(request, "marcar pasta").await?;
+		check_response(response, "marcar pasta").await?;
+		Ok(())
+	}
 
-	let metadata = serde_json::json!({
-		"name": file_name,
-		"parents": [folder_id],
-	});
+	/// A pasta foi criada pelo CloudTray (marcada em `appProperties`), e não escolhida pelo usuário.
+	async fn is_created_by_app(&self, folder_id: &str) -> Result<bool, CloudTrayError> {
+		let headers = self.auth_headers().await?;
 
-	let mut headers = HeaderMap::new();
-	headers.insert(AUTHORIZATION, HeaderValue::from_str(&format!("Bearer {}", tokens.access_token)).unwrap());
+		let request = self
+			.client()
+			.get(self.endpoints().file_url(folder_id))
+			.headers(headers)
+			.query(&[("fields", "appProperties")]);
+		let response = send_with_retry(request, "buscar pasta").await?;
 
-	let mime_type = if file_name.to_lowercase().ends_with(".png") {
-		"image/png"
-	} else if file_name.to_lowercase().ends_with(".jpg") || file_name.to_lowercase().ends_with(".jpeg") {
-		"image/jpeg"
-	} else {
-		"application/octet-stream"
-	};
+		#[derive(Debug, Deserialize)]
+		struct FolderProperties {
+			#[serde(default, rename = "appProperties")]
+			app_properties: std::collections::HashMap<String, String>,
+		}
+
+		let folder: FolderProperties = parse_response(response, "buscar pasta").await?;
 
-	let boundary = "foo_bar_baz";
-	let metadata_part = format!(
-		"--{}\r\nContent-Type: application/json; charset=UTF-8\r\n\r\n{}\r\n",
-		boundary,
-		serde_json::to_string(&metadata).unwrap()
-	);
-	let file_part = format!(
-		"--{}\r\nContent-Type: {}\r\n\r\n",
-		boundary,
-		mime_type
-	);
-	let end_boundary = format!("\r\n--{}--", boundary);
-
-	let mut body = Vec::new();
-	body.extend_from_slice(metadata_part.as_bytes());
-	let _ = window.emit("upload-progress", (file_name.clone(), 10));
-
-	body.extend_from_slice(file_part.as_bytes());
-	let _ = window.emit("upload-progress", (file_name.clone(), 20));
-
-	let chunk_size = file_content.len() / 60;
-	if chunk_size > 0 {
-		for (i, chunk) in file_content.chunks(chunk_size).enumerate() {
-			body.extend_from_slice(chunk);
-			let progress = 20 + ((i as f64 / (file_content.len() as f64 / chunk_size as f64)) * 70.0) as u32;
-			let _ = window.emit("upload-progress", (file_name.clone(), progress));
+		Ok(folder.app_properties.contains_key(APP_FOLDER_PROPERTY))
+	}
+
+	/// Remove o link público da pasta do CloudTray e a move para a lixeira (os arquivos vão junto).
+	/// Falha, com o motivo, quando a pasta não existe ou não foi criada pelo app: uma pasta escolhida
+	/// pelo usuário pode ter outros arquivos e links e nunca é apagada.
+	pub async fn trash_app_folder(&self) -> Result<(), CloudTrayError> {
+		let Some(folder) = self.find_app_folder().await? else {
+			return Err(CloudTrayError::not_found("Nenhuma pasta do CloudTray para mover para a lixeira"));
+		};
+
+		if !self.is_created_by_app(&folder.id).await? {
+			return Err(CloudTrayError::invalid_input(format!(
+				"A pasta {} foi escolhida pelo usuário e não vai para a lixeira",
+				folder.name
+			)));
 		}
-	} else {
-		body.extend_from_slice(&file_content);
-		let _ = window.emit("upload-progress", (file_name.clone(), 90));
+
+		let headers = self.auth_headers().await?;
+		let client = self.client();
+		let endpoints = self.endpoints();
+
+		let request = client
+			.get(endpoints.permissions_url(&folder.id))
+			.headers(headers.clone())
+			.query(&[("fields", "permissions(id,type)")]);
+		let response = send_with_retry(request, "listar permissões da pasta").await?;
+
+		#[derive(Debug, Deserialize)]
+		struct Permission {
+			id: String,
+			#[serde(rename = "type")]
+			kind: String,
+		}
+
+		#[derive(Debug, Deserialize)]
+		struct PermissionList {
+			permissions: Vec<Permission>,
+		}
+
+		let permission_list: PermissionList = parse_response(response, "listar permissões da pasta").await?;
+
+		for permission in permission_list.permissions.into_iter().filter(|permission| permission.kind == "anyone") {
+			let request = client
+				.delete(endpoints.permission_url(&folder.id, &permission.id))
+				.headers(headers.clone());
+			let response = send_with_retry(request, "remover link público da pasta").await?;
+			check_response(response, "remover link público da pasta").await?;
+		}
+
+		let request = client
+			.patch(endpoints.file_url(&folder.id))
+			.headers(headers)
+			.json(&serde_json::json!({ "trashed": true }));
+		let response = send_with_retry(request, "mover pasta para a lixeira").await?;
+		check_response(response, "mover pasta para a lixeira").await?;
+
+		self.track_folder(&self.account_key().await?, None).await
 	}
 
-	body.extend_from_slice(end_boundary.as_bytes());
+	async fn upload_resumable_file(
+		&self,
+		request: UploadRequest,
+		session_key: String,
+		tracker: Arc<ProgressTracker>,
+	) -> Result<DriveFile, CloudTrayError> {
+		let client = self.client();
+
+		let metadata = serde_json::json!({
+			"name": request.file_name,
+			"parents": [request.folder_id],
+		});
 
-	headers.insert(
-		CONTENT_TYPE,
-		HeaderValue::from_str(&format!("multipart/related; boundary={}", boundary)).unwrap()
-	);
+		// O token é buscado (e renovado) de novo sempre que uma sessão começa
+		let access_token = || -> BoxFuture<'_, Result<String, CloudTrayError>> { Box::pin(self.access_token()) };
+
+		upload_resumable(
+			&tracker,
+			&client,
+			&self.endpoints().upload_url(),
+			&access_token,
+			request.source,
+			request.size,
+			&session_key,
+			&metadata,
+			&request.file_name,
+			mime_type_for(&request.file_name),
+		).await
+	}
 
-	let response = client
-		.post("https://www.googleapis.com/upload/drive/v3/files?uploadType=multipart&fields=id,name,webViewLink")
-		.headers(headers.clone())
-		.body(body)
-		.send()
-		.await
-		.map_err(|e| {
-			e.to_string()
-		})?;
+	async fn upload_multipart_file(
+		&self,
+		request: UploadRequest,
+		tracker: Arc<ProgressTracker>,
+	) -> Result<DriveFile, CloudTrayError> {
+		let mut headers = self.auth_headers().await?;
+		let client = self.client();
+
+		let metadata = serde_json::json!({
+			"name": request.file_name,
+			"parents": [request.folder_id],
+		});
+
+		let mime_type = mime_type_for(&request.file_name);
+
+		let boundary = "foo_bar_baz";
+		let metadata_part = format!(
+			"--{}\r\nContent-Type: application/json; charset=UTF-8\r\n\r\n{}\r\n",
+			boundary,
+			serde_json::to_string(&metadata).unwrap()
+		);
+		let file_part = format!(
+			"--{}\r\nContent-Type: {}\r\n\r\n",
+			boundary,
+			mime_type
+		);
+		let end_boundary = format!("\r\n--{}--", boundary);
+
+		// Só os bytes do arquivo entram no progresso; o envelope multipart é ignorado
+		let content = counting_stream(request.source.stream_range(0, request.size).await?, tracker);
+
+		let body_length = metadata_part.len() as u64 + file_part.len() as u64 + request.size + end_boundary.len() as u64;
+		let body: ByteStream = Box::pin(
+			stream::iter([Ok(Bytes::from(metadata_part)), Ok(Bytes::from(file_part))])
+				.chain(content)
+				.chain(stream::once(async move { Ok(Bytes::from(end_boundary)) }))
+		);
+
+		headers.insert(
+			CONTENT_TYPE,
+			HeaderValue::from_str(&format!("multipart/related; boundary={}", boundary)).unwrap()
+		);
+
+		let response = client
+			.post(self.endpoints().upload_url())
+			.query(&[("uploadType", "multipart"), ("fields", "id,name,webViewLink")])
+			.headers(headers)
+			.header(CONTENT_LENGTH, body_length)
+			.body(reqwest::Body::wrap_stream(body))
+			.send()
+			.await
+			.map_err(|e| CloudTrayError::from(e).with_context("Erro ao enviar arquivo"))?;
+
+		parse_response(response, "enviar arquivo").await
+	}
+}
+
+#[async_trait]
+impl StorageProvider for GoogleDriveProvider<'_> {
+	async fn ensure_folder(&self) -> Result<DriveFolder, CloudTrayError> {
+		if let Some(folder) = self.find_app_folder().await? {
+			return Ok(folder);
+		}
 
-	let _ = window.emit("upload-progress", (file_name.clone(), 100));
+		let mut headers = self.auth_headers().await?;
+		let client = self.client();
+		let endpoints = self.endpoints();
 
-	let response_text = response.text().await.map_err(|e| {
-		e.to_string()
-	})?;
+		headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+
+		let folder_metadata = serde_json::json!({
+			"name": APP_FOLDER_NAME,
+			"mimeType": FOLDER_MIME_TYPE,
+			"appProperties": { APP_FOLDER_PROPERTY: "true" }
+		});
+
+		let create_response = client
+			.post(endpoints.files_url())
+			.headers(headers.clone())
+			.json(&folder_metadata)
+			.send()
+			.await
+			.map_err(|e| CloudTrayError::from(e).with_context("Erro ao criar pasta"))?;
+
+		let folder: DriveFolder = parse_response(create_response, "criar pasta").await?;
 
-	if let Ok(file) = serde_json::from_str::<DriveFile>(&response_text) {
 		let permission_body = serde_json::json!({
 			"role": "reader",
 			"type": "anyone"
 		});
 
-		let permission_response = client
-			.post(&format!("https://www.googleapis.com/drive/v3/files/{}/permissions", file.id))
+		// Repetir a mesma permissão "anyone/reader" não cria duplicatas
+		let request = client
+			.post(endpoints.permissions_url(&folder.id))
 			.headers(headers)
-			.json(&permission_body)
-			.send()
-			.await
-			.map_err(|e| format!("Erro ao definir permissões: {}", e))?;
+			.json(&permission_body);
+		let permission_response = send_with_retry(request, "definir permissões da pasta").await?;
+		check_response(permission_response, "definir permissões da pasta").await?;
+
+		self.track_folder(&self.account_key().await?, Some(&folder)).await?;
 
-		if !permission_response.status().is_success() {
-			return Err("Falha ao definir permissões do arquivo".to_string());
+		Ok(folder)
+	}
+
+	async fn upload(&self, request: UploadRequest, tracker: Arc<ProgressTracker>) -> Result<DriveFile, CloudTrayError> {
+		if request.size <= RESUMABLE_THRESHOLD {
+			return self.upload_multipart_file(request, tracker).await;
 		}
 
-		Ok(file)
-	} else {
-		Err("Erro ao fazer parse do arquivo".to_string())
+		let session_key = match &request.source {
+			UploadSource::File(path) => {
+				let file_metadata = tokio::fs::metadata(path)
+					.await
+					.map_err(|e| CloudTrayError::from(e).with_context("Erro ao ler arquivo"))?;
+				path_session_key(path, &request.folder_id, &file_metadata)
+			}
+			UploadSource::Memory(content) => content_session_key(&request.file_name, &request.folder_id, content),
+		};
+
+		self.upload_resumable_file(request, session_key, tracker).await
 	}
-}
 
-#[command]
-pub async fn list_recent_files(credentials: State<'_, GoogleCredentials>) -> Result<Vec<DriveFile>, String> {
-	let folder = get_or_create_app_folder(credentials.clone()).await?;
-
-	let _ = delete_old_files(&folder.id, credentials.clone()).await;
-
-	let tokens = get_tokens(credentials.clone()).await?;
-	let client = reqwest::Client::new();
-	
-	let mut headers = HeaderMap::new();
-	headers.insert(
-		AUTHORIZATION, 
-		HeaderValue::from_str(&format!("Bearer {}", tokens.access_token)).unwrap()
-	);
-
-	let app_folder = get_or_create_app_folder(credentials).await?;
-	
-	let query = format!("'{}' in parents and trashed = false", app_folder.id);
-	
-	let response = client
-		.get("https://www.googleapis.com/drive/v3/files")
-		.headers(headers)
-		.query(&[
-			("q", &query),
-			("orderBy", &"modifiedTime desc".to_string()),
-			("fields", &"files(id,name,webViewLink)".to_string()),
-			("pageSize", &"50".to_string()),
-		])
-		.send()
-		.await
-		.map_err(|e| e.to_string())?;
+	async fn list(&self, folder_id: &str) -> Result<Vec<DriveFile>, CloudTrayError> {
+		let headers = self.auth_headers().await?;
+		let client = self.client();
+
+		let query = format!("'{}' in parents and trashed = false", folder_id);
+
+		let request = client
+			.get(self.endpoints().files_url())
+			.headers(headers)
+			.query(&[
+				("q", &query),
+				("orderBy", &"modifiedTime desc".to_string()),
+				("fields", &"files(id,name,webViewLink)".to_string()),
+				("pageSize", &"50".to_string()),
+			]);
+		let response = send_with_retry(request, "listar arquivos").await?;
+
+		#[derive(Debug, Deserialize)]
+		struct FileList {
+			files: Vec<DriveFile>,
+		}
+
+		let file_list: FileList = parse_response(response, "listar arquivos").await?;
 
-	#[derive(Debug, Deserialize)]
-	struct FileList {
-		files: Vec<DriveFile>,
+		Ok(file_list.files)
 	}
 
-	let response_text = response.text().await.map_err(|e| e.to_string())?;
-	let file_list: FileList = serde_json::from_str(&response_text)
-		.map_err(|e| format!("Error parsing file list: {}", e))?;
+	async fn delete(&self, file_id: &str) -> Result<(), CloudTrayError> {
+		let headers = self.auth_headers().await?;
+		let client = self.client();
 
-	Ok(file_list.files)
-}
+		let request = client
+			.delete(self.endpoints().file_url(file_id))
+			.headers(headers);
+		let response = send_with_retry(request, "excluir arquivo").await?;
+		check_response(response, "excluir arquivo").await?;
 
-#[derive(Debug, Deserialize)]
-struct FileIdOnly {
-	id: String,
-}
+		Ok(())
+	}
 
-async fn delete_old_files(folder_id: &str, credentials: State<'_, GoogleCredentials>) -> Result<(), String> {
-	let config = load_or_create_config().await?;
-	let hours_threshold = config.retention_hours;
-	let tokens = get_tokens(credentials).await?;
-	let client = reqwest::Client::new();
-	
-	let mut headers = HeaderMap::new();
-	headers.insert(
-		AUTHORIZATION, 
-		HeaderValue::from_str(&format!("Bearer {}", tokens.access_token)).unwrap()
-	);
-
-	let threshold_date = Utc::now() - chrono::Duration::hours(hours_threshold);
-	let query = format!(
-		"'{}' in parents and trashed = false and modifiedTime < '{}'",
-		folder_id,
-		threshold_date.format("%Y-%m-%dT%H:%M:%S.%3fZ")
-	);
-
-	let response = client
-		.get("https://www.googleapis.com/drive/v3/files")
-		.headers(headers.clone())
-		.query(&[
-			("q", &query),
-			("fields", &"files(id)".to_string()),
-		])
-		.send()
-		.await
-		.map_err(|e| e.to_string())?;
+	async fn create_share_link(&self, file: &DriveFile) -> Result<String, CloudTrayError> {
+		let headers = self.auth_headers().await?;
+		let client = self.client();
+
+		let permission_body = serde_json::json!({
+			"role": "reader",
+			"type": "anyone"
+		});
 
-	#[derive(Debug, Deserialize)]
-	struct FileList {
-		files: Vec<FileIdOnly>,
+		let request = client
+			.post(self.endpoints().permissions_url(&file.id))
+			.headers(headers)
+			.json(&permission_body);
+		let permission_response = send_with_retry(request, "definir permissões do arquivo").await?;
+		check_response(permission_response, "definir permissões do arquivo").await?;
+
+		Ok(file.web_view_link.clone())
 	}
 
-	let file_list: FileList = response.json().await
-		.map_err(|e| format!("Error parsing file list: {}", e))?;
+	async fn delete_expired(&self, folder_id: &str, older_than: DateTime<Utc>) -> Result<(), CloudTrayError> {
+		let headers = self.auth_headers().await?;
+		let client = self.client();
+
+		let query = format!(
+			"'{}' in parents and trashed = false and modifiedTime < '{}'",
+			folder_id,
+			older_than.format("%Y-%m-%dT%H:%M:%S.%3fZ")
+		);
 
-	for file in file_list.files {
-		let _ = client
-			.delete(&format!("https://www.googleapis.com/drive/v3/files/{}", file.id))
+		let endpoints = self.endpoints();
+		let request = client
+			.get(endpoints.files_url())
 			.headers(headers.clone())
-			.send()
-			.await
-			.map_err(|e| format!("Error deleting file {}: {}", file.id, e))?;
+			.query(&[
+				("q", &query),
+				("fields", &"files(id)".to_string()),
+			]);
+		let response = send_with_retry(request, "listar arquivos expirados").await?;
+
+		#[derive(Debug, Deserialize)]
+		struct FileList {
+			files: Vec<FileIdOnly>,
+		}
+
+		let file_list: FileList = parse_response(response, "listar arquivos expirados").await?;
+
+		for file in file_list.files {
+			let action = format!("excluir arquivo {}", file.id);
+			let request = client
+				.delete(endpoints.file_url(&file.id))
+				.headers(headers.clone());
+			let result = match send_with_retry(request, &action).await {
+				Ok(response) => check_response(response, &action).await.map(|_| ()),
+				Err(err) => Err(err),
+			};
+
+			match result {
+				// Outro cliente (ou uma tentativa anterior) já pode ter removido o arquivo
+				Ok(()) | Err(CloudTrayError::NotFound { .. }) => {}
+				Err(err) => return Err(err),
+			}
+		}
+
+		Ok(())
+	}
+}
+
+/// Converte uma resposta de erro do Google em `CloudTrayError`, usando o `code` e o primeiro `reason` do envelope.
+pub fn google_error(status: reqwest::StatusCode, body: &str, action: &str) -> CloudTrayError {
+	match serde_json::from_str::<GoogleErrorResponse>(body) {
+		Ok(GoogleErrorResponse { error }) => CloudTrayError::from_status(
+			error.code.unwrap_or(status.as_u16()),
+			error.errors.into_iter().find_map(|detail| detail.reason),
+			format!("Erro ao {}: {}", action, error.message.unwrap_or_default()),
+		),
+		Err(_) => CloudTrayError::from_status(status.as_u16(), None, format!("Erro ao {}: {}", action, body)),
+	}
+}
+
+/// Devolve a resposta quando o status é de sucesso; caso contrário, o erro do envelope do Google.
+pub async fn check_response(response: reqwest::Response, action: &str) -> Result<reqwest::Response, CloudTrayError> {
+	let status = response.status();
+	if status.is_success() {
+		return Ok(response);
 	}
 
-	Ok(())
+	let body = response.text().await.unwrap_or_default();
+	Err(google_error(status, &body, action))
 }
 
-#[command]
-pub async fn delete_file(file_id: String, credentials: State<'_, GoogleCredentials>) -> Result<(), String> {
-	let tokens = get_tokens(credentials).await?;
-	let client = reqwest::Client::new();
-	
-	let mut headers = HeaderMap::new();
-	headers.insert(
-		AUTHORIZATION, 
-		HeaderValue::from_str(&format!("Bearer {}", tokens.access_token)).unwrap()
-	);
-
-	client
-		.delete(&format!("https://www.googleapis.com/drive/v3/files/{}", file_id))
-		.headers(headers)
-		.send()
+/// Verifica o status e desserializa o corpo de uma resposta JSON das APIs do Google.
+pub async fn parse_response<T>(response: reqwest::Response, action: &str) -> Result<T, CloudTrayError>
+where
+	T: serde::de::DeserializeOwned,
+{
+	let response_text = check_response(response, action)
+		.await?
+		.text()
 		.await
-		.map_err(|e| format!("Error deleting file: {}", e))?;
+		.map_err(|e| CloudTrayError::from(e).with_context(&format!("Erro ao {}", action)))?;
 
-	Ok(())
-}
\ No newline at end of file
+	serde_json::from_str(&response_text).map_err(|e| {
+		CloudTrayError::unexpected_response(format!("Erro ao {}: resposta inesperada: {}. Resposta: {}", action, e, response_text))
+	})
+}
+
+/// Aceita o id puro ou links como `https://drive.google.com/drive/folders/<id>` e `...?id=<id>`.
+fn folder_id_from_input(input: &str) -> Option<String> {
+	let input = input.trim();
+	let id = match url::Url::parse(input) {
+		Ok(url) => url
+			.query_pairs()
+			.find(|(key, _)| key == "id")
+			.map(|(_, value)| value.into_owned())
+			.or_else(|| {
+				let mut segments = url.path_segments()?;
+				segments.find(|segment| *segment == "folders")?;
+				segments.next().map(String::from)
+			})?,
+		Err(_) => input.to_string(),
+	};
+
+	let valid = !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+	valid.then_some(id)
+}
+
+pub fn mime_type_for(file_name: &str) -> &'static str {
+	let file_name = file_name.to_lowercase();
+	if file_name.ends_with(".png") {
+		"image/png"
+	} else if file_name.ends_with(".jpg") || file_name.ends_with(".jpeg") {
+		"image/jpeg"
+	} else {
+		"application/octet-stream"
+	}
+}
@@ -0,0 +1,81 @@
+use serde::{Deserialize, Serialize};
+
+use crate::config::AppConfig;
+
+/// URLs das APIs do Google. Podem ser trocadas no app_config.json (`google_endpoints`) ou pelas
+/// variáveis de ambiente abaixo, que têm prioridade — útil para apontar para um Drive falso em testes.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct GoogleEndpoints {
+	pub auth_url: String,
+	pub token_url: String,
+	pub device_code_url: String,
+	pub revoke_url: String,
+	pub userinfo_url: String,
+	pub drive_api_url: String,
+	pub drive_upload_url: String,
+}
+
+impl Default for GoogleEndpoints {
+	fn default() -> Self {
+		Self {
+			auth_url: "https://accounts.google.com/o/oauth2/v2/auth".to_string(),
+			token_url: "https://oauth2.googleapis.com/token".to_string(),
+			device_code_url: "https://oauth2.googleapis.com/device/code".to_string(),
+			revoke_url: "https://oauth2.googleapis.com/revoke".to_string(),
+			userinfo_url: "https://openidconnect.googleapis.com/v1/userinfo".to_string(),
+			drive_api_url: "https://www.googleapis.com/drive/v3".to_string(),
+			drive_upload_url: "https://www.googleapis.com/upload/drive/v3".to_string(),
+		}
+	}
+}
+
+impl GoogleEndpoints {
+	pub fn from_config(config: &AppConfig) -> Self {
+		config.google_endpoints.clone().unwrap_or_default().with_env_overrides()
+	}
+
+	fn with_env_overrides(mut self) -> Self {
+		let overrides = [
+			("CLOUDTRAY_GOOGLE_AUTH_URL", &mut self.auth_url),
+			("CLOUDTRAY_GOOGLE_TOKEN_URL", &mut self.token_url),
+			("CLOUDTRAY_GOOGLE_DEVICE_CODE_URL", &mut self.device_code_url),
+			("CLOUDTRAY_GOOGLE_REVOKE_URL", &mut self.revoke_url),
+			("CLOUDTRAY_GOOGLE_USERINFO_URL", &mut self.userinfo_url),
+			("CLOUDTRAY_DRIVE_API_URL", &mut self.drive_api_url),
+			("CLOUDTRAY_DRIVE_UPLOAD_URL", &mut self.drive_upload_url),
+		];
+
+		for (name, url) in overrides {
+			if let Some(value) = std::env::var(name).ok().filter(|value| !value.is_empty()) {
+				*url = value;
+			}
+		}
+
+		self
+	}
+
+	pub fn about_url(&self) -> String {
+		format!("{}/about", self.drive_api_url.trim_end_matches('/'))
+	}
+
+	pub fn files_url(&self) -> String {
+		format!("{}/files", self.drive_api_url.trim_end_matches('/'))
+	}
+
+	pub fn file_url(&self, file_id: &str) -> String {
+		format!("{}/{}", self.files_url(), file_id)
+	}
+
+	pub fn permissions_url(&self, file_id: &str) -> String {
+		format!("{}/permissions", self.file_url(file_id))
+	}
+
+	pub fn permission_url(&self, file_id: &str, permission_id: &str) -> String {
+		format!("{}/{}", self.permissions_url(file_id), permission_id)
+	}
+
+	pub fn upload_url(&self) -> String {
+		format!("{}/files", self.drive_upload_url.trim_end_matches('/'))
+	}
+}
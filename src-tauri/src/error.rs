@@ -0,0 +1,229 @@
+use serde::ser::{SerializeStruct, Serializer};
+use serde::Serialize;
+
+/// Erro devolvido pelos comandos. O frontend decide o texto exibido a partir de `code`
+/// (chaves `errors.<code>` dos arquivos de tradução); `message`, sempre em português, é só para diagnóstico.
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum CloudTrayError {
+	#[error("Não autenticado: {message}")]
+	NotAuthenticated { status: Option<u16>, reason: Option<String>, message: String },
+	#[error("Falha na autorização: {message}")]
+	AuthorizationFailed { status: Option<u16>, reason: Option<String>, message: String },
+	#[error("Erro de rede: {message}")]
+	Network { message: String },
+	#[error("Limite de requisições excedido: {message}")]
+	RateLimited { status: Option<u16>, reason: Option<String>, message: String },
+	#[error("Cota excedida: {message}")]
+	QuotaExceeded { status: Option<u16>, reason: Option<String>, message: String },
+	#[error("Permissão negada: {message}")]
+	PermissionDenied { status: Option<u16>, reason: Option<String>, message: String },
+	#[error("Não encontrado: {message}")]
+	NotFound { status: Option<u16>, reason: Option<String>, message: String },
+	#[error("Erro do servidor: {message}")]
+	Api { status: Option<u16>, reason: Option<String>, message: String },
+	#[error("Dados inválidos: {message}")]
+	InvalidInput { message: String },
+	#[error("Erro de arquivo: {message}")]
+	Io { message: String },
+	#[error("Erro de configuração: {message}")]
+	Config { message: String },
+	#[error("Erro no armazenamento seguro: {message}")]
+	SecureStorage { message: String },
+	#[error("Erro interno: {message}")]
+	Internal { message: String },
+}
+
+impl CloudTrayError {
+	pub fn not_authenticated(message: impl Into<String>) -> Self {
+		Self::NotAuthenticated { status: None, reason: None, message: message.into() }
+	}
+
+	pub fn authorization_failed(message: impl Into<String>) -> Self {
+		Self::AuthorizationFailed { status: None, reason: None, message: message.into() }
+	}
+
+	pub fn network(message: impl Into<String>) -> Self {
+		Self::Network { message: message.into() }
+	}
+
+	pub fn not_found(message: impl Into<String>) -> Self {
+		Self::NotFound { status: None, reason: None, message: message.into() }
+	}
+
+	pub fn invalid_input(message: impl Into<String>) -> Self {
+		Self::InvalidInput { message: message.into() }
+	}
+
+	pub fn io(message: impl Into<String>) -> Self {
+		Self::Io { message: message.into() }
+	}
+
+	pub fn config(message: impl Into<String>) -> Self {
+		Self::Config { message: message.into() }
+	}
+
+	pub fn secure_storage(message: impl Into<String>) -> Self {
+		Self::SecureStorage { message: message.into() }
+	}
+
+	pub fn internal(message: impl Into<String>) -> Self {
+		Self::Internal { message: message.into() }
+	}
+
+	/// Resposta do servidor com status de sucesso mas corpo fora do formato esperado.
+	pub fn unexpected_response(message: impl Into<String>) -> Self {
+		Self::Api { status: None, reason: None, message: message.into() }
+	}
+
+	/// Acrescenta o que estava sendo feito à mensagem, mantendo a classificação do erro.
+	pub fn with_context(mut self, context: &str) -> Self {
+		match &mut self {
+			Self::NotAuthenticated { message, .. }
+			| Self::AuthorizationFailed { message, .. }
+			| Self::Network { message }
+			| Self::RateLimited { message, .. }
+			| Self::QuotaExceeded { message, .. }
+			| Self::PermissionDenied { message, .. }
+			| Self::NotFound { message, .. }
+			| Self::Api { message, .. }
+			| Self::InvalidInput { message }
+			| Self::Io { message }
+			| Self::Config { message }
+			| Self::SecureStorage { message }
+			| Self::Internal { message } => *message = format!("{}: {}", context, message),
+		}
+		self
+	}
+
+	/// Classifica uma resposta HTTP de erro pelo status e, quando houver, pelo `reason` do Google.
+	pub fn from_status(status: u16, reason: Option<String>, message: impl Into<String>) -> Self {
+		let message = message.into();
+		let status_code = Some(status);
+
+		match (status, reason.as_deref()) {
+			(401, _) => Self::NotAuthenticated { status: status_code, reason, message },
+			(403, Some("rateLimitExceeded" | "userRateLimitExceeded")) | (429, _) => {
+				Self::RateLimited { status: status_code, reason, message }
+			}
+			(403, Some("storageQuotaExceeded" | "quotaExceeded" | "dailyLimitExceeded")) | (413 | 507, _) => {
+				Self::QuotaExceeded { status: status_code, reason, message }
+			}
+			(403, _) => Self::PermissionDenied { status: status_code, reason, message },
+			(404 | 410, _) => Self::NotFound { status: status_code, reason, message },
+			_ => Self::Api { status: status_code, reason, message },
+		}
+	}
+
+	/// Falha passageira, que pode dar certo numa nova tentativa: rede, limite de taxa ou erro 5xx do servidor.
+	pub fn is_transient(&self) -> bool {
+		match self {
+			Self::Network { .. } | Self::RateLimited { .. } => true,
+			Self::Api { status: Some(status), .. } => *status >= 500,
+			_ => false,
+		}
+	}
+
+	/// Código estável usado pelo frontend e em logs.
+	pub fn code(&self) -> &'static str {
+		match self {
+			Self::NotAuthenticated { .. } => "not_authenticated",
+			Self::AuthorizationFailed { .. } => "authorization_failed",
+			Self::Network { .. } => "network",
+			Self::RateLimited { .. } => "rate_limited",
+			Self::QuotaExceeded { .. } => "quota_exceeded",
+			Self::PermissionDenied { .. } => "permission_denied",
+			Self::NotFound { .. } => "not_found",
+			Self::Api { .. } => "api",
+			Self::InvalidInput { .. } => "invalid_input",
+			Self::Io { .. } => "io",
+			Self::Config { .. } => "config",
+			Self::SecureStorage { .. } => "secure_storage",
+			Self::Internal { .. } => "internal",
+		}
+	}
+
+	pub fn status(&self) -> Option<u16> {
+		match self {
+			Self::NotAuthenticated { status, .. }
+			| Self::AuthorizationFailed { status, .. }
+			| Self::RateLimited { status, .. }
+			| Self::QuotaExceeded { status, .. }
+			| Self::PermissionDenied { status, .. }
+			| Self::NotFound { status, .. }
+			| Self::Api { status, .. } => *status,
+			_ => None,
+		}
+	}
+
+	pub fn reason(&self) -> Option<&str> {
+		match self {
+			Self::NotAuthenticated { reason, .. }
+			| Self::AuthorizationFailed { reason, .. }
+			| Self::RateLimited { reason, .. }
+			| Self::QuotaExceeded { reason, .. }
+			| Self::PermissionDenied { reason, .. }
+			| Self::NotFound { reason, .. }
+			| Self::Api { reason, .. } => reason.as_deref(),
+			_ => None,
+		}
+	}
+
+	pub fn message(&self) -> &str {
+		match self {
+			Self::NotAuthenticated { message, .. }
+			| Self::AuthorizationFailed { message, .. }
+			| Self::Network { message }
+			| Self::RateLimited { message, .. }
+			| Self::QuotaExceeded { message, .. }
+			| Self::PermissionDenied { message, .. }
+			| Self::NotFound { message, .. }
+			| Self::Api { message, .. }
+			| Self::InvalidInput { message }
+			| Self::Io { message }
+			| Self::Config { message }
+			| Self::SecureStorage { message }
+			| Self::Internal { message } => message,
+		}
+	}
+}
+
+impl Serialize for CloudTrayError {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		let mut state = serializer.serialize_struct("CloudTrayError", 4)?;
+		state.serialize_field("code", self.code())?;
+		state.serialize_field("status", &self.status())?;
+		state.serialize_field("reason", &self.reason())?;
+		state.serialize_field("message", self.message())?;
+		state.end()
+	}
+}
+
+impl From<reqwest::Error> for CloudTrayError {
+	fn from(error: reqwest::Error) -> Self {
+		match error.status() {
+			Some(status) => Self::from_status(status.as_u16(), None, error.to_string()),
+			None if error.is_decode() => Self::unexpected_response(error.to_string()),
+			None => Self::network(error.to_string()),
+		}
+	}
+}
+
+impl From<std::io::Error> for CloudTrayError {
+	fn from(error: std::io::Error) -> Self {
+		match error.kind() {
+			std::io::ErrorKind::NotFound => Self::not_found(error.to_string()),
+			std::io::ErrorKind::PermissionDenied => Self::PermissionDenied {
+				status: None,
+				reason: None,
+				message: error.to_string(),
+			},
+			_ => Self::io(error.to_string()),
+		}
+	}
+}
+
+impl From<serde_json::Error> for CloudTrayError {
+	fn from(error: serde_json::Error) -> Self {
+		Self::internal(error.to_string())
+	}
+}
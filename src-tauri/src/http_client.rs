@@ -0,0 +1,111 @@
+use std::sync::RwLock;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::load_or_create_config;
+use crate::error::CloudTrayError;
+use crate::token_store;
+
+const PROXY_PASSWORD_SECRET_NAME: &str = "proxy_password";
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(15);
+const POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+const TCP_KEEPALIVE: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct NetworkSettings {
+	/// http://, https:// ou socks5://; sem valor, valem os proxies do sistema (HTTP(S)_PROXY)
+	pub proxy_url: Option<String>,
+	/// Hosts que não passam pelo proxy, no formato do NO_PROXY
+	#[serde(default)]
+	pub no_proxy: Vec<String>,
+	/// A senha fica no armazenamento seguro (set_proxy_password)
+	pub proxy_username: Option<String>,
+	/// Arquivo PEM com certificados raiz extras, além dos do sistema
+	pub ca_bundle_path: Option<String>,
+}
+
+/// Cliente HTTP único da aplicação, guardado como estado do Tauri para reaproveitar as conexões
+/// (e o handshake TLS) entre comandos. É recriado quando as configurações de rede mudam.
+pub struct HttpClient(RwLock<reqwest::Client>);
+
+impl HttpClient {
+	pub fn new() -> Result<Self, CloudTrayError> {
+		let client = client_builder()
+			.build()
+			.map_err(|e| CloudTrayError::internal(format!("Erro ao criar cliente HTTP: {}", e)))?;
+
+		Ok(Self(RwLock::new(client)))
+	}
+
+	/// Clonar o `reqwest::Client` só copia a referência ao pool de conexões.
+	pub fn client(&self) -> reqwest::Client {
+		self.0.read().unwrap().clone()
+	}
+
+	pub async fn configure(&self, settings: &NetworkSettings) -> Result<(), CloudTrayError> {
+		let client = build_client(settings).await?;
+		*self.0.write().unwrap() = client;
+		Ok(())
+	}
+
+	/// Aplica as configurações de rede salvas em app_config.json.
+	pub async fn configure_from_saved(&self) -> Result<(), CloudTrayError> {
+		let config = load_or_create_config().await?;
+		self.configure(&config.network).await
+	}
+}
+
+fn client_builder() -> reqwest::ClientBuilder {
+	// Sem timeout total: uploads grandes podem levar muito tempo em links lentos.
+	// O HTTP/2 é negociado via ALPN.
+	reqwest::Client::builder()
+		.user_agent(concat!("CloudTray/", env!("CARGO_PKG_VERSION")))
+		.connect_timeout(CONNECT_TIMEOUT)
+		.pool_idle_timeout(POOL_IDLE_TIMEOUT)
+		.tcp_keepalive(TCP_KEEPALIVE)
+}
+
+async fn build_client(settings: &NetworkSettings) -> Result<reqwest::Client, CloudTrayError> {
+	let mut builder = client_builder();
+
+	if let Some(proxy_url) = settings.proxy_url.as_deref().filter(|url| !url.is_empty()) {
+		let mut proxy = reqwest::Proxy::all(proxy_url)
+			.map_err(|e| CloudTrayError::config(format!("URL do proxy inválida: {}", e)))?;
+
+		if let Some(username) = settings.proxy_username.as_deref().filter(|username| !username.is_empty()) {
+			let password = token_store::load_secret(PROXY_PASSWORD_SECRET_NAME).await?.unwrap_or_default();
+			proxy = proxy.basic_auth(username, &password);
+		}
+
+		proxy = proxy.no_proxy(reqwest::NoProxy::from_string(&settings.no_proxy.join(",")));
+		builder = builder.proxy(proxy);
+	}
+
+	if let Some(path) = settings.ca_bundle_path.as_deref().filter(|path| !path.is_empty()) {
+		let pem = tokio::fs::read(path)
+			.await
+			.map_err(|e| CloudTrayError::from(e).with_context("Erro ao ler certificados raiz"))?;
+		let certificates = reqwest::Certificate::from_pem_bundle(&pem)
+			.map_err(|e| CloudTrayError::config(format!("Certificados raiz inválidos: {}", e)))?;
+
+		for certificate in certificates {
+			builder = builder.add_root_certificate(certificate);
+		}
+	}
+
+	builder
+		.build()
+		.map_err(|e| CloudTrayError::config(format!("Erro ao criar cliente HTTP: {}", e)))
+}
+
+pub async fn set_proxy_password(password: String, http: &HttpClient) -> Result<(), CloudTrayError> {
+	if password.is_empty() {
+		token_store::delete_secret(PROXY_PASSWORD_SECRET_NAME).await?;
+	} else {
+		token_store::store_secret(PROXY_PASSWORD_SECRET_NAME, password).await?;
+	}
+
+	http.configure_from_saved().await
+}
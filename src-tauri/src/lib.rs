@@ -0,0 +1,56 @@
+//! Núcleo do CloudTray: autenticação, contas, configuração e provedores de armazenamento.
+//! Não depende de `State` nem de `Window` do Tauri; o app (e outros front-ends) passam as
+//! credenciais, o cliente HTTP e um callback de progresso diretamente.
+
+pub mod accounts;
+pub mod auth;
+pub mod cli;
+pub mod config;
+pub mod diagnostics;
+pub mod drive;
+pub mod endpoints;
+pub mod error;
+pub mod http_client;
+pub mod progress;
+pub mod queue;
+mod resumable;
+mod retry;
+pub mod s3;
+pub mod storage;
+mod token_store;
+pub mod webdav;
+mod xml;
+
+#[cfg(test)]
+mod tests;
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Credenciais do cliente OAuth e os tokens das contas já carregados em memória.
+pub struct GoogleCredentials {
+	pub client_id: Mutex<String>,
+	pub client_secret: Mutex<String>,
+	pub cached_tokens: Mutex<HashMap<String, drive::GoogleTokens>>,
+	pub refresh_lock: tokio::sync::Mutex<()>,
+	pub pending_authorization: Mutex<Option<auth::PendingAuthorization>>,
+	pub pending_device_authorization: Mutex<Option<auth::PendingDeviceAuthorization>>,
+	pub endpoints: Mutex<endpoints::GoogleEndpoints>,
+	/// Chamado quando o Google recusa o refresh token e a conta precisa de um novo login.
+	pub on_auth_required: Mutex<Option<auth::AuthRequiredCallback>>,
+}
+
+impl GoogleCredentials {
+	pub fn new(client_id: String, client_secret: String, endpoints: endpoints::GoogleEndpoints) -> Self {
+		Self {
+			client_id: Mutex::new(client_id),
+			client_secret: Mutex::new(client_secret),
+			cached_tokens: Mutex::new(HashMap::new()),
+			refresh_lock: tokio::sync::Mutex::new(()),
+			pending_authorization: Mutex::new(None),
+			pending_device_authorization: Mutex::new(None),
+			endpoints: Mutex::new(endpoints),
+			on_auth_required: Mutex::new(None),
+		}
+	}
+}
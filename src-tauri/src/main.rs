@@ -1,9 +1,14 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-mod auth;
-mod drive;
-mod config;
+mod commands;
+
+use cloudtray_core::auth::AuthRequired;
+use cloudtray_core::config;
+use cloudtray_core::diagnostics::{self, Warning};
+use cloudtray_core::endpoints::GoogleEndpoints;
+use cloudtray_core::http_client::HttpClient;
+use cloudtray_core::GoogleCredentials;
 
 use tauri::{
     Manager, SystemTray, SystemTrayEvent, SystemTrayMenu
@@ -19,29 +24,12 @@ use window_shadows::set_shadow;
 
 use tauri_plugin_positioner::{WindowExt, Position};
 
-use std::sync::Mutex;
-use tauri::State;
-
-struct GoogleCredentials {
-    client_id: Mutex<String>,
-    client_secret: Mutex<String>,
-}
-
-#[tauri::command]
-fn set_google_credentials(
-    credentials: State<GoogleCredentials>,
-    client_id: String,
-    client_secret: String,
-) {
-    *credentials.client_id.lock().unwrap() = client_id;
-    *credentials.client_secret.lock().unwrap() = client_secret;
-}
-
 fn main() {
-    let google_credentials = GoogleCredentials {
-        client_id: Mutex::new(String::new()),
-        client_secret: Mutex::new(String::new()),
-    };
+    let google_credentials = GoogleCredentials::new(
+        String::new(),
+        String::new(),
+        GoogleEndpoints::from_config(&config::AppConfig::default()),
+    );
 
     #[cfg(target_os = "macos")]
     let system_tray = SystemTray::new()
@@ -63,10 +51,39 @@ fn main() {
             app.set_activation_policy(ActivationPolicy::Accessory);
 
             let _handle = app.handle();
+
+            // A janela ainda não está ouvindo; os erros ficam nos avisos, lidos por `get_diagnostics`
+            match tauri::async_runtime::block_on(config::load_or_create_config()) {
+                Ok(app_config) => {
+                    let credentials = app.state::<GoogleCredentials>();
+                    *credentials.endpoints.lock().unwrap() = GoogleEndpoints::from_config(&app_config);
+
+                    let http = app.state::<HttpClient>();
+                    if let Err(err) = tauri::async_runtime::block_on(http.configure(&app_config.network)) {
+                        diagnostics::warn(format!("Erro ao aplicar configurações de rede: {}", err));
+                    }
+                }
+                Err(err) => diagnostics::warn(format!("Erro ao carregar configuração: {}", err)),
+            }
+
             let window = app.get_window("tray-window").unwrap();
             let window_clone = window.clone();
             let window_clone_clone = window.clone();
 
+            // Refresh token recusado pelo Google: a janela volta para a tela de login com o motivo
+            let auth_window = window.clone();
+            *app.state::<GoogleCredentials>().on_auth_required.lock().unwrap() = Some(Box::new(move |event: AuthRequired| {
+                let _ = auth_window.emit("auth-required", event);
+            }));
+
+            app.manage(commands::queue::upload_queue(window.clone()));
+
+            // Avisos de retentativas e limpezas vão para a janela, que não tem console no Windows
+            let warning_window = window.clone();
+            diagnostics::set_warning_listener(Some(Box::new(move |warning: Warning| {
+                let _ = warning_window.emit("app-warning", warning);
+            })));
+
             app.listen_global("quit", |_| {
                 std::process::exit(0);
             });
@@ -156,21 +173,40 @@ fn main() {
             }
         })
         .invoke_handler(tauri::generate_handler![
-            set_google_credentials,
-            auth::start_oauth_server, 
-            auth::exchange_auth_code, 
-            auth::save_tokens,
-            auth::get_tokens,
-            auth::logout,
-            drive::upload_file, 
-            drive::get_or_create_app_folder,
-            drive::upload_file_path,
-            drive::list_recent_files,
-            drive::delete_file,
-            config::load_or_create_config,
-            config::save_config,
+            commands::auth::set_google_credentials,
+            commands::auth::start_oauth_server, 
+            commands::auth::build_authorization_url,
+            commands::auth::exchange_auth_code, 
+            commands::auth::start_device_authorization,
+            commands::auth::complete_device_authorization,
+            commands::auth::save_tokens,
+            commands::auth::get_tokens,
+            commands::auth::logout,
+            commands::accounts::list_accounts,
+            commands::accounts::switch_account,
+            commands::accounts::remove_account,
+            commands::storage::upload_file, 
+            commands::storage::get_or_create_app_folder,
+            commands::storage::list_drive_folders,
+            commands::storage::select_drive_folder,
+            commands::storage::upload_file_path,
+            commands::storage::list_recent_files,
+            commands::storage::delete_file,
+            commands::settings::set_s3_secret,
+            commands::settings::set_webdav_password,
+            commands::settings::set_proxy_password,
+            commands::settings::load_or_create_config,
+            commands::settings::save_config,
+            commands::settings::get_diagnostics,
+            commands::queue::enqueue_uploads,
+            commands::queue::list_uploads,
+            commands::queue::cancel_upload,
+            commands::queue::pause_upload,
+            commands::queue::resume_upload,
+            commands::queue::clear_finished_uploads,
         ])
         .manage(google_credentials)
+        .manage(HttpClient::new().expect("error while building the HTTP client"))
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
\ No newline at end of file
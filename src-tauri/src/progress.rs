@@ -0,0 +1,161 @@
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use bytes::Bytes;
+use futures_util::stream::{self, Stream, StreamExt};
+use serde::Serialize;
+use tokio::io::AsyncRead;
+use tokio_util::io::ReaderStream;
+
+// Tamanho dos pedaços entregues ao socket; define a granularidade do progresso
+const PROGRESS_PIECE_SIZE: usize = 64 * 1024;
+const EMIT_INTERVAL_MS: u128 = 250;
+
+pub type ByteStream = Pin<Box<dyn Stream<Item = std::io::Result<Bytes>> + Send + Sync>>;
+
+#[derive(Debug, Serialize, Clone)]
+pub struct UploadProgress {
+	pub file_name: String,
+	pub progress: u32,
+	pub bytes_sent: u64,
+	pub total_bytes: u64,
+	pub bytes_per_second: u64,
+	pub eta_seconds: Option<u64>,
+}
+
+struct TrackerState {
+	bytes_sent: u64,
+	last_emit: Instant,
+	last_emit_bytes: u64,
+	bytes_per_second: f64,
+}
+
+/// Recebe o progresso de um upload; no app é repassado à janela como o evento `upload-progress`.
+pub type ProgressCallback = Box<dyn Fn(UploadProgress) + Send + Sync>;
+
+pub struct ProgressTracker {
+	on_progress: ProgressCallback,
+	file_name: String,
+	total_bytes: u64,
+	state: Mutex<TrackerState>,
+}
+
+impl ProgressTracker {
+	pub fn new(on_progress: ProgressCallback, file_name: String, total_bytes: u64) -> Arc<Self> {
+		let tracker = Arc::new(Self {
+			on_progress,
+			file_name,
+			total_bytes,
+			state: Mutex::new(TrackerState {
+				bytes_sent: 0,
+				last_emit: Instant::now(),
+				last_emit_bytes: 0,
+				bytes_per_second: 0.0,
+			}),
+		});
+
+		let state = tracker.state.lock().unwrap();
+		tracker.emit(&state, false);
+		drop(state);
+
+		tracker
+	}
+
+	/// Reposiciona o contador quando o servidor confirma um offset diferente (ex.: após retomar um upload).
+	pub fn set_position(&self, bytes_sent: u64) {
+		let mut state = self.state.lock().unwrap();
+		state.bytes_sent = bytes_sent;
+		state.last_emit_bytes = bytes_sent;
+		state.last_emit = Instant::now();
+	}
+
+	fn advance(&self, len: u64) {
+		let mut state = self.state.lock().unwrap();
+		state.bytes_sent = (state.bytes_sent + len).min(self.total_bytes);
+
+		let elapsed = state.last_emit.elapsed();
+		if elapsed.as_millis() < EMIT_INTERVAL_MS && state.bytes_sent < self.total_bytes {
+			return;
+		}
+
+		let sent_since_emit = state.bytes_sent.saturating_sub(state.last_emit_bytes) as f64;
+		let instant_rate = sent_since_emit / elapsed.as_secs_f64().max(0.001);
+		state.bytes_per_second = if state.bytes_per_second == 0.0 {
+			instant_rate
+		} else {
+			state.bytes_per_second * 0.7 + instant_rate * 0.3
+		};
+		state.last_emit = Instant::now();
+		state.last_emit_bytes = state.bytes_sent;
+
+		self.emit(&state, false);
+	}
+
+	/// Emite 100% somente depois que o Drive confirmou o arquivo.
+	pub fn finish(&self) {
+		let mut state = self.state.lock().unwrap();
+		state.bytes_sent = self.total_bytes;
+		self.emit(&state, true);
+	}
+
+	fn emit(&self, state: &TrackerState, finished: bool) {
+		let progress = if finished {
+			100
+		} else if self.total_bytes == 0 {
+			0
+		} else {
+			((state.bytes_sent as f64 / self.total_bytes as f64) * 100.0).min(99.0) as u32
+		};
+
+		let eta_seconds = if state.bytes_per_second > 0.0 {
+			Some((self.total_bytes.saturating_sub(state.bytes_sent) as f64 / state.bytes_per_second).ceil() as u64)
+		} else {
+			None
+		};
+
+		(self.on_progress)(UploadProgress {
+			file_name: self.file_name.clone(),
+			progress,
+			bytes_sent: state.bytes_sent,
+			total_bytes: self.total_bytes,
+			bytes_per_second: state.bytes_per_second as u64,
+			eta_seconds: if finished { Some(0) } else { eta_seconds },
+		});
+	}
+}
+
+/// Divide o conteúdo em pedaços pequenos sem copiar os bytes.
+pub fn bytes_stream(content: Bytes) -> ByteStream {
+	let mut pieces = Vec::with_capacity(content.len() / PROGRESS_PIECE_SIZE + 1);
+	let mut offset = 0;
+	while offset < content.len() {
+		let end = (offset + PROGRESS_PIECE_SIZE).min(content.len());
+		pieces.push(Ok(content.slice(offset..end)));
+		offset = end;
+	}
+	Box::pin(stream::iter(pieces))
+}
+
+/// Lê o conteúdo direto do disco, mantendo em memória apenas um pedaço por vez.
+pub fn reader_stream<R>(reader: R) -> ByteStream
+where
+	R: AsyncRead + Send + Sync + 'static,
+{
+	Box::pin(ReaderStream::with_capacity(reader, PROGRESS_PIECE_SIZE))
+}
+
+/// Contabiliza cada pedaço no momento em que é consumido; útil quando o conteúdo é só parte do corpo.
+pub fn counting_stream(content: ByteStream, tracker: Arc<ProgressTracker>) -> ByteStream {
+	Box::pin(content.map(move |piece| {
+		if let Ok(piece) = &piece {
+			tracker.advance(piece.len() as u64);
+		}
+		piece
+	}))
+}
+
+/// Corpo de requisição que contabiliza cada pedaço no momento em que o hyper o consome para escrever no socket.
+pub fn counting_body(content: ByteStream, tracker: Arc<ProgressTracker>) -> reqwest::Body {
+	reqwest::Body::wrap_stream(counting_stream(content, tracker))
+}
@@ -0,0 +1,266 @@
+//! Fila de uploads com limite de envios simultâneos, novas tentativas em erros transitórios,
+//! pausa e cancelamento. Quem usa a fila informa como enviar cada job e recebe as mudanças de estado por callback.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use futures_util::future::BoxFuture;
+use serde::Serialize;
+use tokio::task::JoinHandle;
+
+use crate::drive::DriveFile;
+use crate::error::CloudTrayError;
+
+const MAX_ATTEMPTS: u32 = 3;
+const RETRY_BASE_DELAY_SECS: u64 = 2;
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum JobState {
+	Queued,
+	Running,
+	Retrying,
+	Paused,
+	Completed,
+	Failed,
+	Cancelled,
+}
+
+impl JobState {
+	fn is_active(self) -> bool {
+		matches!(self, JobState::Running | JobState::Retrying)
+	}
+
+	pub fn is_finished(self) -> bool {
+		matches!(self, JobState::Completed | JobState::Failed | JobState::Cancelled)
+	}
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct UploadJob {
+	pub id: u64,
+	pub file_path: String,
+	pub file_name: String,
+	pub folder_id: String,
+	pub account_id: String,
+	pub state: JobState,
+	pub attempts: u32,
+	pub error: Option<CloudTrayError>,
+	pub file: Option<DriveFile>,
+}
+
+/// Envia o arquivo de um job; no app chama `storage::upload_file_path` com as credenciais e o progresso da janela.
+pub type UploadWorker = Box<dyn Fn(UploadJob) -> BoxFuture<'static, Result<DriveFile, CloudTrayError>> + Send + Sync>;
+
+/// Recebe cada mudança de estado de um job; no app é repassada à janela como o evento `upload-job`.
+pub type JobCallback = Box<dyn Fn(UploadJob) + Send + Sync>;
+
+struct QueueState {
+	jobs: Mutex<Vec<UploadJob>>,
+	handles: Mutex<HashMap<u64, JoinHandle<()>>>,
+	next_id: AtomicU64,
+	max_parallel: AtomicUsize,
+	worker: UploadWorker,
+	on_job: JobCallback,
+}
+
+#[derive(Clone)]
+pub struct UploadQueue(Arc<QueueState>);
+
+impl UploadQueue {
+	pub fn new(worker: UploadWorker, on_job: JobCallback) -> Self {
+		Self(Arc::new(QueueState {
+			jobs: Mutex::new(Vec::new()),
+			handles: Mutex::new(HashMap::new()),
+			next_id: AtomicU64::new(1),
+			max_parallel: AtomicUsize::new(2),
+			worker,
+			on_job,
+		}))
+	}
+
+	/// Enfileira os arquivos para a conta e a pasta informadas; `max_parallel` vem da configuração.
+	pub fn enqueue(
+		&self,
+		file_paths: Vec<String>,
+		folder_id: String,
+		account_id: String,
+		max_parallel: usize,
+	) -> Result<Vec<UploadJob>, CloudTrayError> {
+		self.0.max_parallel.store(max_parallel.max(1), Ordering::SeqCst);
+
+		// Todos os caminhos são validados antes: um nome inválido não deixa jobs na fila sem agendamento
+		let file_names = file_paths
+			.iter()
+			.map(|file_path| {
+				std::path::Path::new(file_path)
+					.file_name()
+					.and_then(|name| name.to_str())
+					.map(|name| name.to_string())
+					.ok_or_else(|| CloudTrayError::invalid_input(format!("Nome do arquivo inválido: {}", file_path)))
+			})
+			.collect::<Result<Vec<_>, _>>()?;
+
+		let mut queued = Vec::new();
+		{
+			let mut jobs = self.0.jobs.lock().unwrap();
+			for (file_path, file_name) in file_paths.into_iter().zip(file_names) {
+				let job = UploadJob {
+					id: self.0.next_id.fetch_add(1, Ordering::SeqCst),
+					file_path,
+					file_name,
+					folder_id: folder_id.clone(),
+					account_id: account_id.clone(),
+					state: JobState::Queued,
+					attempts: 0,
+					error: None,
+					file: None,
+				};
+				(self.0.on_job)(job.clone());
+				jobs.push(job.clone());
+				queued.push(job);
+			}
+		}
+
+		self.schedule();
+		Ok(queued)
+	}
+
+	pub fn list(&self) -> Vec<UploadJob> {
+		self.0.jobs.lock().unwrap().clone()
+	}
+
+	pub fn cancel(&self, job_id: u64) -> Result<(), CloudTrayError> {
+		self.stop(job_id, JobState::Cancelled)?;
+		self.schedule();
+		Ok(())
+	}
+
+	pub fn pause(&self, job_id: u64) -> Result<(), CloudTrayError> {
+		self.stop(job_id, JobState::Paused)?;
+		self.schedule();
+		Ok(())
+	}
+
+	pub fn resume(&self, job_id: u64) -> Result<(), CloudTrayError> {
+		{
+			let mut jobs = self.0.jobs.lock().unwrap();
+			let job = jobs
+				.iter_mut()
+				.find(|job| job.id == job_id)
+				.ok_or_else(|| CloudTrayError::not_found("Upload não encontrado"))?;
+
+			if job.state != JobState::Paused {
+				return Err(CloudTrayError::invalid_input("Upload não está pausado"));
+			}
+
+			// Arquivos grandes continuam da sessão resumable salva em disco
+			job.state = JobState::Queued;
+			(self.0.on_job)(job.clone());
+		}
+
+		self.schedule();
+		Ok(())
+	}
+
+	pub fn clear_finished(&self) {
+		self.0.jobs.lock().unwrap().retain(|job| !job.state.is_finished());
+	}
+
+	fn update<F>(&self, job_id: u64, change: F)
+	where
+		F: FnOnce(&mut UploadJob),
+	{
+		let mut jobs = self.0.jobs.lock().unwrap();
+		if let Some(job) = jobs.iter_mut().find(|job| job.id == job_id) {
+			// Um job cancelado ou pausado não volta a mudar de estado pelo worker
+			if job.state.is_finished() || job.state == JobState::Paused {
+				return;
+			}
+			change(job);
+			(self.0.on_job)(job.clone());
+		}
+	}
+
+	fn stop(&self, job_id: u64, new_state: JobState) -> Result<(), CloudTrayError> {
+		let mut jobs = self.0.jobs.lock().unwrap();
+		let job = jobs
+			.iter_mut()
+			.find(|job| job.id == job_id)
+			.ok_or_else(|| CloudTrayError::not_found("Upload não encontrado"))?;
+
+		if job.state.is_finished() {
+			return Err(CloudTrayError::invalid_input("Upload já finalizado"));
+		}
+
+		if let Some(handle) = self.0.handles.lock().unwrap().remove(&job_id) {
+			handle.abort();
+		}
+
+		job.state = new_state;
+		(self.0.on_job)(job.clone());
+		Ok(())
+	}
+
+	fn schedule(&self) {
+		let mut jobs = self.0.jobs.lock().unwrap();
+		let active = jobs.iter().filter(|job| job.state.is_active()).count();
+		let available = self.0.max_parallel.load(Ordering::SeqCst).saturating_sub(active);
+
+		for job in jobs.iter_mut().filter(|job| job.state == JobState::Queued).take(available) {
+			job.state = JobState::Running;
+			job.error = None;
+			(self.0.on_job)(job.clone());
+
+			let task = tokio::spawn(self.clone().run_job(job.clone()));
+			self.0.handles.lock().unwrap().insert(job.id, task);
+		}
+	}
+
+	async fn run_job(self, job: UploadJob) {
+		let mut attempts = 0;
+		let result = loop {
+			attempts += 1;
+			match (self.0.worker)(job.clone()).await {
+				Ok(file) => break Ok(file),
+				// Erros permanentes (autenticação, permissão, arquivo inexistente) não melhoram com outra tentativa
+				Err(err) if attempts < MAX_ATTEMPTS && err.is_transient() => {
+					self.update(job.id, |job| {
+						job.state = JobState::Retrying;
+						job.attempts = attempts;
+						job.error = Some(err);
+					});
+
+					tokio::time::sleep(Duration::from_secs(RETRY_BASE_DELAY_SECS * 2u64.pow(attempts - 1))).await;
+
+					self.update(job.id, |job| job.state = JobState::Running);
+				}
+				Err(err) => break Err(err),
+			}
+		};
+
+		{
+			// O lock de jobs garante que o schedule já registrou o handle desta tarefa
+			let _jobs = self.0.jobs.lock().unwrap();
+			self.0.handles.lock().unwrap().remove(&job.id);
+		}
+
+		self.update(job.id, |job| {
+			job.attempts = attempts;
+			match result {
+				Ok(file) => {
+					job.state = JobState::Completed;
+					job.file = Some(file);
+				}
+				Err(err) => {
+					job.state = JobState::Failed;
+					job.error = Some(err);
+				}
+			}
+		});
+
+		self.schedule();
+	}
+}
@@ -0,0 +1,337 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+use futures_util::future::BoxFuture;
+use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_LENGTH, CONTENT_RANGE, CONTENT_TYPE, LOCATION, RANGE};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use crate::config::config_dir;
+use crate::drive::{check_response, google_error, DriveFile};
+use crate::error::CloudTrayError;
+use crate::retry::{is_retryable_status, retry_after, send_with_retry, RetryBudget};
+use crate::progress::{counting_body, ByteStream, ProgressTracker};
+use crate::storage::UploadSource;
+
+// Arquivos acima deste tamanho usam uploadType=resumable
+pub const RESUMABLE_THRESHOLD: u64 = 5 * 1024 * 1024;
+
+// O Drive exige chunks múltiplos de 256 KiB
+const CHUNK_SIZE: u64 = 32 * 256 * 1024;
+const SESSION_MAX_AGE_HOURS: i64 = 6 * 24;
+
+static SESSIONS_LOCK: Mutex<()> = Mutex::const_new(());
+
+/// Devolve um access token válido; é chamado a cada sessão iniciada, porque um upload longo
+/// pode passar da validade do token e uma sessão expirada precisa ser recriada com um token novo.
+pub type AccessTokenProvider<'a> = dyn Fn() -> BoxFuture<'a, Result<String, CloudTrayError>> + Send + Sync + 'a;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct UploadSession {
+	session_uri: String,
+	file_name: String,
+	total_size: u64,
+	created_at: i64,
+}
+
+/// Chave da sessão para uploads feitos a partir de um caminho no disco.
+pub fn path_session_key(file_path: &Path, folder_id: &str, metadata: &std::fs::Metadata) -> String {
+	let modified = metadata
+		.modified()
+		.ok()
+		.and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+		.map(|duration| duration.as_secs())
+		.unwrap_or(0);
+
+	format!("path:{}:{}:{}:{}", folder_id, file_path.display(), metadata.len(), modified)
+}
+
+/// Chave da sessão para uploads vindos da memória (drag and drop).
+pub fn content_session_key(file_name: &str, folder_id: &str, content: &[u8]) -> String {
+	let mut hasher = DefaultHasher::new();
+	content.hash(&mut hasher);
+
+	format!("content:{}:{}:{}:{:x}", folder_id, file_name, content.len(), hasher.finish())
+}
+
+fn sessions_path() -> Result<PathBuf, CloudTrayError> {
+	Ok(config_dir()?.join("upload_sessions.json"))
+}
+
+async fn read_sessions() -> HashMap<String, UploadSession> {
+	let Ok(path) = sessions_path() else {
+		return HashMap::new();
+	};
+
+	match tokio::fs::read_to_string(&path).await {
+		Ok(sessions_str) => serde_json::from_str(&sessions_str).unwrap_or_default(),
+		Err(_) => HashMap::new(),
+	}
+}
+
+async fn write_sessions(sessions: &HashMap<String, UploadSession>) -> Result<(), CloudTrayError> {
+	let path = sessions_path()?;
+	if let Some(parent) = path.parent() {
+		tokio::fs::create_dir_all(parent)
+			.await
+			.map_err(|e| CloudTrayError::from(e).with_context("Erro ao criar diretório de configuração"))?;
+	}
+
+	let sessions_json = serde_json::to_string_pretty(sessions)
+		.map_err(|e| CloudTrayError::internal(format!("Erro ao serializar sessões de upload: {}", e)))?;
+
+	tokio::fs::write(&path, sessions_json)
+		.await
+		.map_err(|e| CloudTrayError::from(e).with_context("Erro ao salvar sessões de upload"))
+}
+
+async fn load_session(key: &str) -> Option<UploadSession> {
+	let _guard = SESSIONS_LOCK.lock().await;
+	let mut sessions = read_sessions().await;
+
+	let max_age = Utc::now().timestamp() - SESSION_MAX_AGE_HOURS * 3600;
+	let before = sessions.len();
+	sessions.retain(|_, session| session.created_at > max_age);
+	if sessions.len() != before {
+		let _ = write_sessions(&sessions).await;
+	}
+
+	sessions.get(key).cloned()
+}
+
+async fn store_session(key: &str, session: UploadSession) -> Result<(), CloudTrayError> {
+	let _guard = SESSIONS_LOCK.lock().await;
+	let mut sessions = read_sessions().await;
+	sessions.insert(key.to_string(), session);
+	write_sessions(&sessions).await
+}
+
+async fn remove_session(key: &str) {
+	let _guard = SESSIONS_LOCK.lock().await;
+	let mut sessions = read_sessions().await;
+	if sessions.remove(key).is_some() {
+		let _ = write_sessions(&sessions).await;
+	}
+}
+
+enum SessionStatus {
+	Incomplete(u64),
+	Complete(DriveFile),
+	Expired,
+	// Falha temporária (429, 5xx ou limite de taxa); o Retry-After vem junto quando informado
+	Retry(CloudTrayError, Option<Duration>),
+}
+
+async fn start_session(
+	client: &reqwest::Client,
+	upload_url: &str,
+	access_token: &str,
+	metadata: &serde_json::Value,
+	mime_type: &str,
+	total_size: u64,
+) -> Result<String, CloudTrayError> {
+	let mut headers = HeaderMap::new();
+	headers.insert(AUTHORIZATION, HeaderValue::from_str(&format!("Bearer {}", access_token)).unwrap());
+	headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json; charset=UTF-8"));
+	headers.insert("X-Upload-Content-Type", HeaderValue::from_str(mime_type).unwrap());
+	headers.insert("X-Upload-Content-Length", HeaderValue::from(total_size));
+
+	// Uma sessão criada a mais só expira sem uso, então o início pode ser repetido
+	let request = client
+		.post(upload_url)
+		.query(&[("uploadType", "resumable"), ("fields", "id,name,webViewLink")])
+		.headers(headers)
+		.json(metadata);
+	let response = send_with_retry(request, "iniciar upload").await?;
+
+	check_response(response, "iniciar upload")
+		.await?
+		.headers()
+		.get(LOCATION)
+		.and_then(|value| value.to_str().ok())
+		.map(|value| value.to_string())
+		.ok_or_else(|| CloudTrayError::unexpected_response("Resposta sem URI de sessão de upload"))
+}
+
+async fn parse_session_response(response: reqwest::Response) -> Result<SessionStatus, CloudTrayError> {
+	match response.status() {
+		StatusCode::OK | StatusCode::CREATED => {
+			let response_text = response.text().await?;
+			serde_json::from_str::<DriveFile>(&response_text)
+				.map(SessionStatus::Complete)
+				.map_err(|e| CloudTrayError::unexpected_response(format!("Erro ao fazer parse do arquivo: {}. Resposta: {}", e, response_text)))
+		}
+		StatusCode::PERMANENT_REDIRECT => {
+			let next_offset = response
+				.headers()
+				.get(RANGE)
+				.and_then(|value| value.to_str().ok())
+				.and_then(|value| value.rsplit('-').next())
+				.and_then(|last_byte| last_byte.parse::<u64>().ok())
+				.map(|last_byte| last_byte + 1)
+				.unwrap_or(0);
+			Ok(SessionStatus::Incomplete(next_offset))
+		}
+		StatusCode::NOT_FOUND | StatusCode::GONE => Ok(SessionStatus::Expired),
+		status => {
+			let delay = retry_after(&response);
+			let response_text = response.text().await.unwrap_or_default();
+			let error = google_error(status, &response_text, "enviar arquivo");
+
+			if is_retryable_status(status) || matches!(error, CloudTrayError::RateLimited { .. }) {
+				Ok(SessionStatus::Retry(error, delay))
+			} else {
+				Err(error)
+			}
+		}
+	}
+}
+
+async fn query_session(client: &reqwest::Client, session_uri: &str, total_size: u64) -> Result<SessionStatus, CloudTrayError> {
+	let response = client
+		.put(session_uri)
+		.header(CONTENT_LENGTH, 0)
+		.header(CONTENT_RANGE, format!("bytes */{}", total_size))
+		.send()
+		.await
+		.map_err(|e| CloudTrayError::from(e).with_context("Erro ao consultar sessão de upload"))?;
+
+	parse_session_response(response).await
+}
+
+async fn put_chunk(
+	client: &reqwest::Client,
+	session_uri: &str,
+	chunk: ByteStream,
+	chunk_len: u64,
+	offset: u64,
+	total_size: u64,
+	tracker: &Arc<ProgressTracker>,
+) -> Result<SessionStatus, CloudTrayError> {
+	let end = offset + chunk_len - 1;
+
+	let response = client
+		.put(session_uri)
+		.header(CONTENT_LENGTH, chunk_len)
+		.header(CONTENT_RANGE, format!("bytes {}-{}/{}", offset, end, total_size))
+		.body(counting_body(chunk, tracker.clone()))
+		.send()
+		.await
+		.map_err(|e| CloudTrayError::from(e).with_context("Erro ao enviar parte do arquivo"))?;
+
+	parse_session_response(response).await
+}
+
+/// Envia o arquivo usando uploadType=resumable, retomando uma sessão salva quando existir.
+#[allow(clippy::too_many_arguments)]
+pub async fn upload_resumable(
+	tracker: &Arc<ProgressTracker>,
+	client: &reqwest::Client,
+	upload_url: &str,
+	access_token: &AccessTokenProvider<'_>,
+	source: UploadSource,
+	total_size: u64,
+	session_key: &str,
+	metadata: &serde_json::Value,
+	file_name: &str,
+	mime_type: &str,
+) -> Result<DriveFile, CloudTrayError> {
+	let mut offset = 0;
+
+	let mut session_uri = match load_session(session_key).await {
+		Some(session) if session.total_size == total_size => {
+			match query_session(client, &session.session_uri, total_size).await {
+				Ok(SessionStatus::Incomplete(next_offset)) => {
+					offset = next_offset;
+					Some(session.session_uri)
+				}
+				Ok(SessionStatus::Complete(file)) => {
+					remove_session(session_key).await;
+					return Ok(file);
+				}
+				_ => {
+					remove_session(session_key).await;
+					None
+				}
+			}
+		}
+		_ => None,
+	};
+
+	let new_session = || async {
+		let uri = start_session(client, upload_url, &access_token().await?, metadata, mime_type, total_size).await?;
+		store_session(session_key, UploadSession {
+			session_uri: uri.clone(),
+			file_name: file_name.to_string(),
+			total_size,
+			created_at: Utc::now().timestamp(),
+		}).await?;
+		Ok::<_, CloudTrayError>(uri)
+	};
+
+	let mut session_uri = match session_uri.take() {
+		Some(uri) => uri,
+		None => new_session().await?,
+	};
+	let mut budget = RetryBudget::new();
+
+	loop {
+		tracker.set_position(offset);
+
+		let len = CHUNK_SIZE.min(total_size - offset);
+		let chunk = source.stream_range(offset, len).await?;
+
+		let failure = match put_chunk(client, &session_uri, chunk, len, offset, total_size, tracker).await {
+			Ok(SessionStatus::Retry(err, delay)) => Err((err, delay)),
+			Ok(status) => Ok(status),
+			Err(err @ CloudTrayError::Network { .. }) => Err((err, None)),
+			Err(err) => return Err(err),
+		};
+
+		let status = match failure {
+			Ok(status) => status,
+			Err((err, delay)) => {
+				if !budget.wait("enviar parte do arquivo", err.message(), delay).await {
+					return Err(err);
+				}
+				// Pergunta ao Drive quantos bytes chegaram antes de reenviar
+				match query_session(client, &session_uri, total_size).await {
+					Ok(SessionStatus::Retry(..)) | Err(_) => continue,
+					Ok(status) => status,
+				}
+			}
+		};
+
+		match status {
+			SessionStatus::Complete(file) => {
+				remove_session(session_key).await;
+				return Ok(file);
+			}
+			SessionStatus::Incomplete(next_offset) => {
+				if next_offset > offset {
+					budget = RetryBudget::new();
+				} else if !budget.wait("enviar parte do arquivo", "o Drive não confirmou novos bytes", None).await {
+					// Sem progresso o mesmo trecho seria reenviado para sempre
+					return Err(CloudTrayError::unexpected_response("O upload não avançou após várias tentativas"));
+				}
+				offset = next_offset;
+			}
+			SessionStatus::Expired => {
+				// Sessões do Drive expiram (ex.: depois de uma semana); o upload recomeça numa sessão nova
+				remove_session(session_key).await;
+				if !budget.wait("enviar parte do arquivo", "sessão de upload expirada", None).await {
+					return Err(CloudTrayError::not_found("Sessão de upload expirada, tente novamente"));
+				}
+				session_uri = new_session().await?;
+				offset = 0;
+			}
+			SessionStatus::Retry(..) => continue,
+		}
+	}
+}
@@ -0,0 +1,128 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use reqwest::header::RETRY_AFTER;
+use reqwest::{RequestBuilder, Response, StatusCode};
+
+use crate::diagnostics;
+use crate::drive::google_error;
+use crate::error::CloudTrayError;
+
+const MAX_RETRIES: u32 = 5;
+const BASE_DELAY_MS: u64 = 1000;
+const MAX_DELAY_MS: u64 = 32 * 1000;
+// Soma máxima das esperas de uma mesma operação
+const MAX_TOTAL_RETRY_TIME: Duration = Duration::from_secs(2 * 60);
+
+static RETRY_COUNT: AtomicU64 = AtomicU64::new(0);
+
+/// Total de retentativas desde o início do processo.
+pub fn retry_count() -> u64 {
+	RETRY_COUNT.load(Ordering::Relaxed)
+}
+
+pub fn is_retryable_status(status: StatusCode) -> bool {
+	matches!(status.as_u16(), 429 | 500 | 502 | 503 | 504)
+}
+
+/// Lê o cabeçalho Retry-After, que pode vir em segundos ou como data HTTP.
+pub fn retry_after(response: &Response) -> Option<Duration> {
+	let value = response.headers().get(RETRY_AFTER)?.to_str().ok()?.trim();
+
+	if let Ok(secs) = value.parse::<u64>() {
+		return Some(Duration::from_secs(secs));
+	}
+
+	let date = DateTime::parse_from_rfc2822(value).ok()?;
+	(date.with_timezone(&Utc) - Utc::now()).to_std().ok()
+}
+
+/// Espera exponencial com jitter: metade do intervalo é fixa e a outra metade aleatória.
+fn backoff_delay(attempt: u32) -> Duration {
+	let delay = BASE_DELAY_MS.saturating_mul(1 << attempt.min(16)).min(MAX_DELAY_MS);
+	let half = delay / 2;
+	Duration::from_millis(half + rand::thread_rng().gen_range(0..=half))
+}
+
+/// Controla as novas tentativas de uma operação, limitando a quantidade e o tempo total de espera.
+pub struct RetryBudget {
+	waited: Duration,
+	attempts: u32,
+}
+
+impl RetryBudget {
+	pub fn new() -> Self {
+		Self { waited: Duration::ZERO, attempts: 0 }
+	}
+
+	/// Aguarda antes da próxima tentativa; devolve `false` quando o limite já foi atingido.
+	pub async fn wait(&mut self, action: &str, cause: &str, retry_after: Option<Duration>) -> bool {
+		if self.attempts >= MAX_RETRIES {
+			return false;
+		}
+
+		let delay = retry_after.unwrap_or_else(|| backoff_delay(self.attempts));
+		// Só as esperas contam; uma requisição ou um trecho lento numa conexão ruim não consome o limite
+		if self.waited + delay > MAX_TOTAL_RETRY_TIME {
+			return false;
+		}
+
+		self.attempts += 1;
+		self.waited += delay;
+		RETRY_COUNT.fetch_add(1, Ordering::Relaxed);
+		diagnostics::warn(format!(
+			"Falha ao {} ({}); tentativa {} de {} em {:.1}s",
+			action,
+			cause,
+			self.attempts,
+			MAX_RETRIES,
+			delay.as_secs_f32()
+		));
+
+		tokio::time::sleep(delay).await;
+		true
+	}
+}
+
+/// Envia uma requisição idempotente às APIs do Google, repetindo-a em falhas de rede, 429, 5xx
+/// e limites de taxa reportados como 403. Corpos em streaming não podem ser clonados e são enviados uma única vez.
+pub async fn send_with_retry(request: RequestBuilder, action: &str) -> Result<Response, CloudTrayError> {
+	let mut budget = RetryBudget::new();
+
+	loop {
+		let Some(attempt) = request.try_clone() else {
+			return request
+				.send()
+				.await
+				.map_err(|e| CloudTrayError::from(e).with_context(&format!("Erro ao {}", action)));
+		};
+
+		match attempt.send().await {
+			Ok(response) if is_retryable_status(response.status()) => {
+				let delay = retry_after(&response);
+				if !budget.wait(action, response.status().as_str(), delay).await {
+					return Ok(response);
+				}
+			}
+			Ok(response) if response.status() == StatusCode::FORBIDDEN => {
+				// O Google sinaliza limite de taxa com 403 e o motivo no corpo
+				let status = response.status();
+				let body = response.text().await.unwrap_or_default();
+				let error = google_error(status, &body, action);
+
+				if !matches!(error, CloudTrayError::RateLimited { .. }) || !budget.wait(action, error.message(), None).await {
+					return Err(error);
+				}
+			}
+			Ok(response) => return Ok(response),
+			Err(e) if e.is_timeout() || e.is_connect() => {
+				if !budget.wait(action, &e.to_string(), None).await {
+					return Err(CloudTrayError::from(e).with_context(&format!("Erro ao {}", action)));
+				}
+			}
+			Err(e) => return Err(CloudTrayError::from(e).with_context(&format!("Erro ao {}", action))),
+		}
+	}
+}
@@ -0,0 +1,507 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use reqwest::header::{AUTHORIZATION, CONTENT_LENGTH, CONTENT_TYPE, ETAG};
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use url::Url;
+
+use crate::config::AppConfig;
+use crate::drive::{mime_type_for, DriveFile, DriveFolder};
+use crate::error::CloudTrayError;
+use crate::progress::{counting_body, ProgressTracker};
+use crate::storage::{StorageProvider, UploadRequest};
+use crate::token_store;
+use crate::xml;
+
+const SECRET_NAME: &str = "s3_secret_access_key";
+
+// O corpo é enviado em streaming, então o hash do payload não é assinado
+const UNSIGNED_PAYLOAD: &str = "UNSIGNED-PAYLOAD";
+
+// O S3 exige partes de pelo menos 5 MiB, exceto a última
+const MULTIPART_THRESHOLD: u64 = 16 * 1024 * 1024;
+const PART_SIZE: u64 = 8 * 1024 * 1024;
+
+// Validade máxima de uma URL pré-assinada com SigV4
+const MAX_PRESIGN_SECS: i64 = 7 * 24 * 3600;
+const MAX_LISTED_FILES: usize = 50;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct S3Settings {
+	/// Ex.: https://s3.us-east-1.amazonaws.com, https://<conta>.r2.cloudflarestorage.com ou http://localhost:9000 (MinIO)
+	pub endpoint: String,
+	pub region: String,
+	pub bucket: String,
+	#[serde(default = "default_prefix")]
+	pub prefix: String,
+	pub access_key_id: String,
+	/// MinIO e a maioria dos servidores locais só aceitam o bucket no caminho da URL.
+	#[serde(default)]
+	pub force_path_style: bool,
+}
+
+fn default_prefix() -> String {
+	"CloudTray".to_string()
+}
+
+struct S3Object {
+	key: String,
+	last_modified: DateTime<Utc>,
+}
+
+pub struct S3Provider {
+	settings: S3Settings,
+	secret_access_key: String,
+	retention_hours: i64,
+	client: reqwest::Client,
+}
+
+impl S3Provider {
+	pub async fn from_config(config: &AppConfig, client: &reqwest::Client) -> Result<Self, CloudTrayError> {
+		let settings = config.s3.clone().ok_or_else(|| CloudTrayError::config("Armazenamento S3 não configurado"))?;
+		let secret_access_key = token_store::load_secret(SECRET_NAME)
+			.await?
+			.ok_or_else(|| CloudTrayError::config("Chave secreta do S3 não configurada"))?;
+
+		Ok(Self {
+			settings,
+			secret_access_key,
+			retention_hours: config.retention_hours,
+			client: client.clone(),
+		})
+	}
+
+	fn folder_prefix(&self) -> String {
+		let prefix = self.settings.prefix.trim_matches('/');
+		if prefix.is_empty() {
+			String::new()
+		} else {
+			format!("{}/", prefix)
+		}
+	}
+
+	fn url(&self, key: &str, query: &[(&str, &str)]) -> Result<Url, CloudTrayError> {
+		let mut url = Url::parse(&self.settings.endpoint)
+			.map_err(|e| CloudTrayError::config(format!("Endpoint S3 inválido: {}", e)))?;
+
+		let path = if self.settings.force_path_style {
+			if key.is_empty() {
+				format!("/{}", self.settings.bucket)
+			} else {
+				format!("/{}/{}", self.settings.bucket, uri_encode(key, false))
+			}
+		} else {
+			let host = url.host_str().ok_or_else(|| CloudTrayError::config("Endpoint S3 inválido"))?.to_string();
+			url.set_host(Some(&format!("{}.{}", self.settings.bucket, host)))
+				.map_err(|e| CloudTrayError::config(format!("Endpoint S3 inválido: {}", e)))?;
+			format!("/{}", uri_encode(key, false))
+		};
+		url.set_path(&path);
+
+		let mut pairs: Vec<(String, String)> = query
+			.iter()
+			.map(|(name, value)| (uri_encode(name, true), uri_encode(value, true)))
+			.collect();
+		pairs.sort();
+		let canonical_query = pairs
+			.iter()
+			.map(|(name, value)| format!("{}={}", name, value))
+			.collect::<Vec<_>>()
+			.join("&");
+		url.set_query(if canonical_query.is_empty() { None } else { Some(&canonical_query) });
+
+		Ok(url)
+	}
+
+	fn scope(&self, date: &str) -> String {
+		format!("{}/{}/s3/aws4_request", date, self.settings.region)
+	}
+
+	/// Assinatura SigV4; `headers` precisa estar em minúsculas e em ordem alfabética.
+	fn signature(&self, method: &str, url: &Url, headers: &[(&str, String)], now: DateTime<Utc>) -> String {
+		let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+		let date = now.format("%Y%m%d").to_string();
+
+		let canonical_headers: String = headers
+			.iter()
+			.map(|(name, value)| format!("{}:{}\n", name, value.trim()))
+			.collect();
+		let signed_headers = headers.iter().map(|(name, _)| *name).collect::<Vec<_>>().join(";");
+
+		let canonical_request = format!(
+			"{}\n{}\n{}\n{}\n{}\n{}",
+			method,
+			url.path(),
+			url.query().unwrap_or(""),
+			canonical_headers,
+			signed_headers,
+			UNSIGNED_PAYLOAD
+		);
+		let string_to_sign = format!(
+			"AWS4-HMAC-SHA256\n{}\n{}\n{}",
+			amz_date,
+			self.scope(&date),
+			hex::encode(Sha256::digest(canonical_request.as_bytes()))
+		);
+
+		let k_date = hmac_sha256(format!("AWS4{}", self.secret_access_key).as_bytes(), date.as_bytes());
+		let k_region = hmac_sha256(&k_date, self.settings.region.as_bytes());
+		let k_service = hmac_sha256(&k_region, b"s3");
+		let k_signing = hmac_sha256(&k_service, b"aws4_request");
+
+		hex::encode(hmac_sha256(&k_signing, string_to_sign.as_bytes()))
+	}
+
+	fn request(&self, method: Method, key: &str, query: &[(&str, &str)]) -> Result<reqwest::RequestBuilder, CloudTrayError> {
+		let url = self.url(key, query)?;
+		let now = Utc::now();
+		let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+
+		let headers = [
+			("host", host_header(&url)),
+			("x-amz-content-sha256", UNSIGNED_PAYLOAD.to_string()),
+			("x-amz-date", amz_date.clone()),
+		];
+		let signature = self.signature(method.as_str(), &url, &headers, now);
+		let authorization = format!(
+			"AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders=host;x-amz-content-sha256;x-amz-date, Signature={}",
+			self.settings.access_key_id,
+			self.scope(&now.format("%Y%m%d").to_string()),
+			signature
+		);
+
+		Ok(self.client
+			.request(method, url)
+			.header("x-amz-content-sha256", UNSIGNED_PAYLOAD)
+			.header("x-amz-date", amz_date)
+			.header(AUTHORIZATION, authorization))
+	}
+
+	fn presigned_url(&self, key: &str, expires_secs: i64) -> Result<String, CloudTrayError> {
+		let now = Utc::now();
+		let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+		let credential = format!("{}/{}", self.settings.access_key_id, self.scope(&now.format("%Y%m%d").to_string()));
+		let expires = expires_secs.to_string();
+
+		let url = self.url(key, &[
+			("X-Amz-Algorithm", "AWS4-HMAC-SHA256"),
+			("X-Amz-Credential", &credential),
+			("X-Amz-Date", &amz_date),
+			("X-Amz-Expires", &expires),
+			("X-Amz-SignedHeaders", "host"),
+		])?;
+		let signature = self.signature("GET", &url, &[("host", host_header(&url))], now);
+
+		Ok(format!("{}&X-Amz-Signature={}", url, signature))
+	}
+
+	/// O link expira junto com a retenção configurada, respeitando o limite de 7 dias do SigV4.
+	fn share_link(&self, key: &str) -> Result<String, CloudTrayError> {
+		self.presigned_url(key, (self.retention_hours * 3600).clamp(1, MAX_PRESIGN_SECS))
+	}
+
+	async fn list_objects(&self, prefix: &str) -> Result<Vec<S3Object>, CloudTrayError> {
+		let mut objects = Vec::new();
+		let mut continuation_token: Option<String> = None;
+
+		loop {
+			let mut query = vec![("list-type", "2"), ("prefix", prefix)];
+			if let Some(token) = &continuation_token {
+				query.push(("continuation-token", token.as_str()));
+			}
+
+			let response = self.request(Method::GET, "", &query)?
+				.send()
+				.await
+				.map_err(|e| CloudTrayError::from(e).with_context("Erro ao listar arquivos"))?;
+			let body = check_response(response, "listar arquivos")
+				.await?
+				.text()
+				.await?;
+
+			for entry in xml::elements(&body, "Contents") {
+				let key = xml::first(entry, "Key");
+				let last_modified = xml::first(entry, "LastModified")
+					.and_then(|value| DateTime::parse_from_rfc3339(&value).ok())
+					.map(|value| value.with_timezone(&Utc));
+
+				if let (Some(key), Some(last_modified)) = (key, last_modified) {
+					if !key.ends_with('/') {
+						objects.push(S3Object { key, last_modified });
+					}
+				}
+			}
+
+			continuation_token = xml::first(&body, "NextContinuationToken");
+			if continuation_token.is_none() {
+				break;
+			}
+		}
+
+		Ok(objects)
+	}
+
+	async fn upload_parts(
+		&self,
+		key: &str,
+		upload_id: &str,
+		request: &UploadRequest,
+		tracker: &Arc<ProgressTracker>,
+	) -> Result<Vec<String>, CloudTrayError> {
+		let mut etags = Vec::new();
+		let mut offset = 0;
+
+		while offset < request.size {
+			let len = PART_SIZE.min(request.size - offset);
+			let chunk = request.source.stream_range(offset, len).await?;
+			let part_number = (etags.len() + 1).to_string();
+
+			let response = self.request(Method::PUT, key, &[("partNumber", &part_number), ("uploadId", upload_id)])?
+				.header(CONTENT_LENGTH, len)
+				.body(counting_body(chunk, Arc::clone(tracker)))
+				.send()
+				.await
+				.map_err(|e| CloudTrayError::from(e).with_context("Erro ao enviar parte do arquivo"))?;
+			let response = check_response(response, "enviar parte do arquivo").await?;
+
+			let etag = response
+				.headers()
+				.get(ETAG)
+				.and_then(|value| value.to_str().ok())
+				.ok_or_else(|| CloudTrayError::unexpected_response("Resposta sem ETag"))?
+				.to_string();
+			etags.push(etag);
+			offset += len;
+		}
+
+		Ok(etags)
+	}
+
+	async fn upload_multipart(&self, key: &str, request: &UploadRequest, tracker: &Arc<ProgressTracker>) -> Result<(), CloudTrayError> {
+		let response = self.request(Method::POST, key, &[("uploads", "")])?
+			.header(CONTENT_TYPE, mime_type_for(&request.file_name))
+			.send()
+			.await
+			.map_err(|e| CloudTrayError::from(e).with_context("Erro ao iniciar upload"))?;
+		let body = check_response(response, "iniciar upload")
+			.await?
+			.text()
+			.await?;
+		let upload_id = xml::first(&body, "UploadId").ok_or_else(|| CloudTrayError::unexpected_response("Resposta sem UploadId"))?;
+
+		let etags = match self.upload_parts(key, &upload_id, request, tracker).await {
+			Ok(etags) => etags,
+			Err(err) => {
+				// Partes órfãs continuam ocupando espaço no bucket até o upload ser abortado
+				if let Ok(abort) = self.request(Method::DELETE, key, &[("uploadId", &upload_id)]) {
+					let _ = abort.send().await;
+				}
+				return Err(err);
+			}
+		};
+
+		let parts: String = etags
+			.iter()
+			.enumerate()
+			.map(|(index, etag)| format!("<Part><PartNumber>{}</PartNumber><ETag>{}</ETag></Part>", index + 1, etag))
+			.collect();
+
+		let response = self.request(Method::POST, key, &[("uploadId", &upload_id)])?
+			.header(CONTENT_TYPE, "application/xml")
+			.body(format!("<CompleteMultipartUpload>{}</CompleteMultipartUpload>", parts))
+			.send()
+			.await
+			.map_err(|e| CloudTrayError::from(e).with_context("Erro ao concluir upload"))?;
+		let body = check_response(response, "concluir upload")
+			.await?
+			.text()
+			.await?;
+
+		// O S3 pode responder 200 e mesmo assim reportar um erro no corpo
+		if body.contains("<Error>") {
+			return Err(CloudTrayError::Api {
+				status: None,
+				reason: xml::first(&body, "Code"),
+				message: format!("Erro no S3 ao concluir upload: {}", error_message(&body)),
+			});
+		}
+
+		Ok(())
+	}
+}
+
+#[async_trait]
+impl StorageProvider for S3Provider {
+	async fn ensure_folder(&self) -> Result<DriveFolder, CloudTrayError> {
+		let response = self.request(Method::HEAD, "", &[])?
+			.send()
+			.await
+			.map_err(|e| CloudTrayError::from(e).with_context("Erro ao acessar bucket"))?;
+		check_response(response, "acessar bucket").await?;
+
+		Ok(DriveFolder {
+			id: self.folder_prefix(),
+			name: format!("{}/{}", self.settings.bucket, self.folder_prefix()),
+		})
+	}
+
+	async fn upload(&self, request: UploadRequest, tracker: Arc<ProgressTracker>) -> Result<DriveFile, CloudTrayError> {
+		let key = object_key(&request.folder_id, &request.file_name);
+
+		if request.size > MULTIPART_THRESHOLD {
+			self.upload_multipart(&key, &request, &tracker).await?;
+		} else {
+			let content = request.source.stream_range(0, request.size).await?;
+			let response = self.request(Method::PUT, &key, &[])?
+				.header(CONTENT_LENGTH, request.size)
+				.header(CONTENT_TYPE, mime_type_for(&request.file_name))
+				.body(counting_body(content, tracker))
+				.send()
+				.await
+				.map_err(|e| CloudTrayError::from(e).with_context("Erro ao enviar arquivo"))?;
+			check_response(response, "enviar arquivo").await?;
+		}
+
+		Ok(DriveFile {
+			id: key,
+			name: request.file_name,
+			web_view_link: String::new(),
+		})
+	}
+
+	async fn list(&self, folder_id: &str) -> Result<Vec<DriveFile>, CloudTrayError> {
+		let mut objects = self.list_objects(folder_id).await?;
+		objects.sort_by_key(|object| std::cmp::Reverse(object.last_modified));
+
+		objects
+			.into_iter()
+			.take(MAX_LISTED_FILES)
+			.map(|object| {
+				Ok(DriveFile {
+					name: object.key.rsplit('/').next().unwrap_or(&object.key).to_string(),
+					web_view_link: self.share_link(&object.key)?,
+					id: object.key,
+				})
+			})
+			.collect()
+	}
+
+	async fn delete(&self, file_id: &str) -> Result<(), CloudTrayError> {
+		let response = self.request(Method::DELETE, file_id, &[])?
+			.send()
+			.await
+			.map_err(|e| CloudTrayError::from(e).with_context("Erro ao apagar arquivo"))?;
+		check_response(response, "remover arquivo").await?;
+
+		Ok(())
+	}
+
+	async fn create_share_link(&self, file: &DriveFile) -> Result<String, CloudTrayError> {
+		self.share_link(&file.id)
+	}
+
+	/// Só apaga objetos enviados pelo CloudTray: os que estão sob o prefixo configurado (nunca o bucket
+	/// inteiro) e seguem o formato de `object_key`, independentemente da pasta informada pelo frontend.
+	async fn delete_expired(&self, _folder_id: &str, older_than: DateTime<Utc>) -> Result<(), CloudTrayError> {
+		let prefix = self.folder_prefix();
+		if prefix.is_empty() {
+			return Err(CloudTrayError::config("A limpeza automática do S3 precisa de um prefixo; defina \"prefix\" nas configurações"));
+		}
+
+		let objects = self.list_objects(&prefix).await?;
+		let expired = objects
+			.iter()
+			.filter(|object| object.last_modified < older_than && is_object_key(&prefix, &object.key));
+
+		for object in expired {
+			self.delete(&object.key).await?;
+		}
+
+		Ok(())
+	}
+}
+
+/// Cada upload ganha uma "pasta" própria (data e sufixo aleatório), para que arquivos com o mesmo nome
+/// não sobrescrevam objetos já compartilhados e o link continue terminando no nome original.
+pub(crate) fn object_key(folder_id: &str, file_name: &str) -> String {
+	format!(
+		"{}{}-{:08x}/{}",
+		folder_id,
+		Utc::now().format("%Y%m%dT%H%M%S"),
+		rand::random::<u32>(),
+		file_name
+	)
+}
+
+/// A chave tem o formato de `object_key`: `<prefixo><AAAAMMDDTHHMMSS>-<8 hex>/<nome>`.
+pub(crate) fn is_object_key(prefix: &str, key: &str) -> bool {
+	let Some((upload_dir, file_name)) = key.strip_prefix(prefix).and_then(|rest| rest.split_once('/')) else {
+		return false;
+	};
+	let Some((timestamp, suffix)) = upload_dir.split_once('-') else {
+		return false;
+	};
+
+	!file_name.is_empty()
+		&& !file_name.contains('/')
+		&& chrono::NaiveDateTime::parse_from_str(timestamp, "%Y%m%dT%H%M%S").is_ok()
+		&& suffix.len() == 8
+		&& suffix.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+	let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC aceita chaves de qualquer tamanho");
+	mac.update(data);
+	mac.finalize().into_bytes().to_vec()
+}
+
+fn host_header(url: &Url) -> String {
+	let host = url.host_str().unwrap_or_default();
+	match url.port() {
+		Some(port) => format!("{}:{}", host, port),
+		None => host.to_string(),
+	}
+}
+
+/// Codificação exigida pelo SigV4: só os caracteres não reservados ficam intactos.
+fn uri_encode(value: &str, encode_slash: bool) -> String {
+	let mut encoded = String::with_capacity(value.len());
+	for byte in value.bytes() {
+		match byte {
+			b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => encoded.push(byte as char),
+			b'/' if !encode_slash => encoded.push('/'),
+			_ => encoded.push_str(&format!("%{:02X}", byte)),
+		}
+	}
+	encoded
+}
+
+fn error_message(body: &str) -> String {
+	xml::first(body, "Message").unwrap_or_else(|| body.to_string())
+}
+
+async fn check_response(response: reqwest::Response, action: &str) -> Result<reqwest::Response, CloudTrayError> {
+	if response.status().is_success() {
+		return Ok(response);
+	}
+
+	let status = response.status();
+	let body = response.text().await.unwrap_or_default();
+	Err(CloudTrayError::from_status(
+		status.as_u16(),
+		xml::first(&body, "Code"),
+		format!("Erro no S3 ao {}: {}", action, error_message(&body)),
+	))
+}
+
+pub async fn set_s3_secret(secret_access_key: String) -> Result<(), CloudTrayError> {
+	if secret_access_key.is_empty() {
+		token_store::delete_secret(SECRET_NAME).await
+	} else {
+		token_store::store_secret(SECRET_NAME, secret_access_key).await
+	}
+}
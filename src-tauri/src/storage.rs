@@ -0,0 +1,222 @@
+use std::io::SeekFrom;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+use crate::config::load_or_create_config;
+use crate::diagnostics;
+use crate::drive::{DriveFile, DriveFolder, GoogleDriveProvider};
+use crate::error::CloudTrayError;
+use crate::http_client::HttpClient;
+use crate::s3::S3Provider;
+use crate::webdav::WebDavProvider;
+use crate::progress::{bytes_stream, reader_stream, ByteStream, ProgressCallback, ProgressTracker};
+use crate::GoogleCredentials;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ProviderKind {
+	#[default]
+	GoogleDrive,
+	S3,
+	#[serde(rename = "webdav")]
+	WebDav,
+}
+
+pub enum UploadSource {
+	Memory(Bytes),
+	File(PathBuf),
+}
+
+impl UploadSource {
+	pub async fn stream_range(&self, offset: u64, len: u64) -> Result<ByteStream, CloudTrayError> {
+		match self {
+			UploadSource::Memory(content) => {
+				let start = offset as usize;
+				let end = (offset + len) as usize;
+				Ok(bytes_stream(content.slice(start..end)))
+			}
+			UploadSource::File(path) => {
+				let mut file = tokio::fs::File::open(path)
+					.await
+					.map_err(|e| CloudTrayError::from(e).with_context("Erro ao ler arquivo"))?;
+				file.seek(SeekFrom::Start(offset))
+					.await
+					.map_err(|e| CloudTrayError::from(e).with_context("Erro ao ler arquivo"))?;
+				Ok(reader_stream(file.take(len)))
+			}
+		}
+	}
+}
+
+pub struct UploadRequest {
+	pub source: UploadSource,
+	pub size: u64,
+	pub file_name: String,
+	pub folder_id: String,
+}
+
+/// Operações que cada destino de armazenamento precisa oferecer para o tray funcionar.
+#[async_trait]
+pub trait StorageProvider: Send + Sync {
+	/// Pasta (ou prefixo) onde o CloudTray guarda os uploads, criada se ainda não existir.
+	async fn ensure_folder(&self) -> Result<DriveFolder, CloudTrayError>;
+
+	async fn upload(&self, request: UploadRequest, tracker: Arc<ProgressTracker>) -> Result<DriveFile, CloudTrayError>;
+
+	async fn list(&self, folder_id: &str) -> Result<Vec<DriveFile>, CloudTrayError>;
+
+	async fn delete(&self, file_id: &str) -> Result<(), CloudTrayError>;
+
+	/// Torna o arquivo acessível por link e devolve a URL para compartilhar.
+	async fn create_share_link(&self, file: &DriveFile) -> Result<String, CloudTrayError>;
+
+	/// Remove os arquivos da pasta modificados antes de `older_than` (retenção).
+	async fn delete_expired(&self, folder_id: &str, older_than: DateTime<Utc>) -> Result<(), CloudTrayError>;
+}
+
+pub async fn configured_provider<'a>(
+	account_id: Option<String>,
+	credentials: &'a GoogleCredentials,
+	http: &'a HttpClient,
+) -> Result<Box<dyn StorageProvider + 'a>, CloudTrayError> {
+	let config = load_or_create_config().await?;
+
+	match config.provider {
+		ProviderKind::GoogleDrive => Ok(Box::new(GoogleDriveProvider::new(account_id, credentials, http))),
+		ProviderKind::S3 => Ok(Box::new(S3Provider::from_config(&config, &http.client()).await?)),
+		ProviderKind::WebDav => Ok(Box::new(WebDavProvider::from_config(&config, &http.client()).await?)),
+	}
+}
+
+pub async fn delete_old_files(provider: &dyn StorageProvider, folder_id: &str) -> Result<(), CloudTrayError> {
+	let config = load_or_create_config().await?;
+	let threshold_date = Utc::now() - chrono::Duration::hours(config.retention_hours);
+
+	provider.delete_expired(folder_id, threshold_date).await
+}
+
+async fn upload_and_share(
+	provider: &dyn StorageProvider,
+	request: UploadRequest,
+	on_progress: ProgressCallback,
+) -> Result<DriveFile, CloudTrayError> {
+	// A limpeza não deve impedir o upload
+	if let Err(err) = delete_old_files(provider, &request.folder_id).await {
+		diagnostics::warn(format!("Erro ao remover arquivos antigos: {}", err));
+	}
+
+	let tracker = ProgressTracker::new(on_progress, request.file_name.clone(), request.size);
+
+	let mut file = provider.upload(request, Arc::clone(&tracker)).await?;
+	file.web_view_link = provider.create_share_link(&file).await?;
+	tracker.finish();
+
+	Ok(file)
+}
+
+pub async fn get_or_create_app_folder(
+	account_id: Option<String>,
+	credentials: &GoogleCredentials,
+	http: &HttpClient,
+) -> Result<DriveFolder, CloudTrayError> {
+	let provider = configured_provider(account_id, credentials, http).await?;
+	provider.ensure_folder().await
+}
+
+/// Pastas do CloudTray no Google Drive que podem ser escolhidas como destino dos uploads.
+pub async fn list_drive_folders(
+	account_id: Option<String>,
+	credentials: &GoogleCredentials,
+	http: &HttpClient,
+) -> Result<Vec<DriveFolder>, CloudTrayError> {
+	GoogleDriveProvider::new(account_id, credentials, http).list_folders().await
+}
+
+/// Troca a pasta de destino da conta no Google Drive por uma pasta existente (id ou link).
+pub async fn select_drive_folder(
+	folder: String,
+	account_id: Option<String>,
+	credentials: &GoogleCredentials,
+	http: &HttpClient,
+) -> Result<DriveFolder, CloudTrayError> {
+	GoogleDriveProvider::new(account_id, credentials, http).select_folder(&folder).await
+}
+
+pub async fn upload_file_path(
+	file_path: String,
+	folder_id: String,
+	account_id: Option<String>,
+	credentials: &GoogleCredentials,
+	http: &HttpClient,
+	on_progress: ProgressCallback,
+) -> Result<DriveFile, CloudTrayError> {
+	let provider = configured_provider(account_id, credentials, http).await?;
+
+	let path = PathBuf::from(&file_path);
+	let file_name = path
+		.file_name()
+		.and_then(|name| name.to_str())
+		.ok_or_else(|| CloudTrayError::invalid_input("Nome do arquivo inválido"))?
+		.to_string();
+
+	let file_metadata = tokio::fs::metadata(&path)
+		.await
+		.map_err(|e| CloudTrayError::from(e).with_context("Erro ao ler arquivo"))?;
+
+	upload_and_share(provider.as_ref(), UploadRequest {
+		source: UploadSource::File(path),
+		size: file_metadata.len(),
+		file_name,
+		folder_id,
+	}, on_progress).await
+}
+
+pub async fn upload_file(
+	file_content: Vec<u8>,
+	file_name: String,
+	folder_id: String,
+	account_id: Option<String>,
+	credentials: &GoogleCredentials,
+	http: &HttpClient,
+	on_progress: ProgressCallback,
+) -> Result<DriveFile, CloudTrayError> {
+	let provider = configured_provider(account_id, credentials, http).await?;
+
+	upload_and_share(provider.as_ref(), UploadRequest {
+		size: file_content.len() as u64,
+		source: UploadSource::Memory(Bytes::from(file_content)),
+		file_name,
+		folder_id,
+	}, on_progress).await
+}
+
+pub async fn list_recent_files(
+	account_id: Option<String>,
+	credentials: &GoogleCredentials,
+	http: &HttpClient,
+) -> Result<Vec<DriveFile>, CloudTrayError> {
+	let provider = configured_provider(account_id, credentials, http).await?;
+	let folder = provider.ensure_folder().await?;
+
+	if let Err(err) = delete_old_files(provider.as_ref(), &folder.id).await {
+		diagnostics::warn(format!("Erro ao remover arquivos antigos: {}", err));
+	}
+
+	provider.list(&folder.id).await
+}
+
+pub async fn delete_file(
+	file_id: String,
+	account_id: Option<String>,
+	credentials: &GoogleCredentials,
+	http: &HttpClient,
+) -> Result<(), CloudTrayError> {
+	let provider = configured_provider(account_id, credentials, http).await?;
+	provider.delete(&file_id).await
+}
@@ -0,0 +1,265 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use super::fake_google::{DEVICE_CODE, REFRESHED_ACCESS_TOKEN, TEST_EMAIL, USER_CODE, VALID_ACCESS_TOKEN, VALID_REFRESH_TOKEN};
+use super::{expired_tokens, valid_tokens, TestContext};
+use crate::accounts::list_accounts;
+use crate::config::{config_dir, load_or_create_config, migrate_legacy_files};
+use crate::auth::{complete_device_authorization, get_tokens, logout, start_device_authorization, AuthRequired};
+use crate::error::CloudTrayError;
+use crate::storage::{get_or_create_app_folder, select_drive_folder};
+use crate::token_store;
+
+#[tokio::test]
+async fn get_tokens_refreshes_expired_tokens() {
+	let ctx = TestContext::new().await;
+	ctx.sign_in(expired_tokens()).await;
+
+	let tokens = get_tokens(None, &ctx.credentials, &ctx.http).await.unwrap();
+	assert_eq!(tokens.access_token, REFRESHED_ACCESS_TOKEN);
+	assert_eq!(tokens.refresh_token, VALID_REFRESH_TOKEN);
+
+	let requests = ctx.google.token_requests();
+	assert_eq!(requests.len(), 1);
+	assert_eq!(requests[0]["grant_type"], "refresh_token");
+	assert_eq!(requests[0]["refresh_token"], VALID_REFRESH_TOKEN);
+	assert_eq!(requests[0]["client_id"], "test-client");
+
+	// Os tokens novos ficam salvos e em cache; a próxima chamada não faz outro refresh
+	let stored = token_store::load_tokens(TEST_EMAIL).await.unwrap().unwrap();
+	assert_eq!(stored.access_token, REFRESHED_ACCESS_TOKEN);
+
+	get_tokens(None, &ctx.credentials, &ctx.http).await.unwrap();
+	assert_eq!(ctx.google.token_requests().len(), 1);
+}
+
+#[tokio::test]
+async fn get_tokens_keeps_fresh_tokens() {
+	let ctx = TestContext::new().await;
+	ctx.sign_in(valid_tokens()).await;
+
+	let tokens = get_tokens(None, &ctx.credentials, &ctx.http).await.unwrap();
+	assert_eq!(tokens.access_token, valid_tokens().access_token);
+	assert!(ctx.google.token_requests().is_empty());
+}
+
+#[tokio::test]
+async fn get_tokens_reports_revoked_refresh_token() {
+	let ctx = TestContext::new().await;
+	ctx.sign_in(crate::drive::GoogleTokens {
+		refresh_token: "revoked-refresh-token".to_string(),
+		..expired_tokens()
+	}).await;
+
+	let notified = Arc::new(Mutex::new(Vec::new()));
+	let events = Arc::clone(&notified);
+	*ctx.credentials.on_auth_required.lock().unwrap() = Some(Box::new(move |event: AuthRequired| events.lock().unwrap().push(event)));
+
+	let error = get_tokens(None, &ctx.credentials, &ctx.http).await.unwrap_err();
+	assert!(matches!(
+		error,
+		CloudTrayError::AuthorizationFailed { reason: Some(ref reason), .. } if reason == "invalid_grant"
+	));
+
+	let events = notified.lock().unwrap().clone();
+	assert_eq!(events.len(), 1);
+	assert_eq!(events[0].account_id, TEST_EMAIL);
+	assert_eq!(events[0].reason, "invalid_grant");
+
+	// Os tokens recusados são descartados: a próxima chamada pede login sem tentar outro refresh
+	assert!(token_store::load_tokens(TEST_EMAIL).await.unwrap().is_none());
+	let error = get_tokens(None, &ctx.credentials, &ctx.http).await.unwrap_err();
+	assert_eq!(error.code(), "not_authenticated");
+	assert_eq!(ctx.google.token_requests().len(), 1);
+}
+
+#[tokio::test]
+async fn device_authorization_polls_until_approved_and_saves_account() {
+	let ctx = TestContext::new().await;
+
+	let device = start_device_authorization(&ctx.credentials, &ctx.http).await.unwrap();
+	assert_eq!(device.user_code, USER_CODE);
+	assert_eq!(device.verification_url, "https://www.google.com/device");
+
+	let account = complete_device_authorization(&ctx.credentials, &ctx.http).await.unwrap();
+	assert_eq!(account.email, TEST_EMAIL);
+
+	// Uma consulta pendente e outra aprovada
+	let requests = ctx.google.token_requests();
+	assert_eq!(requests.len(), 2);
+	assert!(requests.iter().all(|request| request["device_code"] == DEVICE_CODE));
+
+	let stored = token_store::load_tokens(TEST_EMAIL).await.unwrap().unwrap();
+	assert_eq!(stored.access_token, VALID_ACCESS_TOKEN);
+	assert_eq!(list_accounts().await.unwrap().active.as_deref(), Some(TEST_EMAIL));
+}
+
+#[tokio::test]
+async fn device_authorization_slows_down_when_asked() {
+	let ctx = TestContext::new().await;
+	ctx.google.slow_down_device_polls();
+
+	start_device_authorization(&ctx.credentials, &ctx.http).await.unwrap();
+	let started = Instant::now();
+	complete_device_authorization(&ctx.credentials, &ctx.http).await.unwrap();
+
+	// O fake pede intervalo 0; depois do `slow_down` a consulta seguinte espera mais 5 segundos
+	assert_eq!(ctx.google.token_requests().len(), 2);
+	assert!(started.elapsed() >= Duration::from_secs(5));
+}
+
+#[tokio::test]
+async fn missing_client_id_is_a_config_error() {
+	let ctx = TestContext::new().await;
+	*ctx.credentials.client_id.lock().unwrap() = String::new();
+
+	let error = start_device_authorization(&ctx.credentials, &ctx.http).await.unwrap_err();
+	assert_eq!(error.code(), "config");
+	assert_eq!(error.message(), "Credenciais do Google não configuradas");
+}
+
+#[tokio::test]
+async fn logout_revokes_tokens_and_trashes_app_folder() {
+	let ctx = TestContext::new().await;
+	ctx.sign_in(valid_tokens()).await;
+	let folder_id = get_or_create_app_folder(None, &ctx.credentials, &ctx.http).await.unwrap().id;
+	assert_eq!(ctx.google.permissions().len(), 1);
+
+	let report = logout(None, true, &ctx.credentials, &ctx.http).await.unwrap();
+	assert_eq!(report.account_id.as_deref(), Some(TEST_EMAIL));
+	assert!(report.tokens_revoked);
+	assert!(report.folder_trashed);
+	assert!(report.failures.is_empty());
+
+	assert_eq!(ctx.google.revoked_tokens(), vec![VALID_ACCESS_TOKEN, VALID_REFRESH_TOKEN]);
+	assert!(ctx.google.file(&folder_id).unwrap().trashed);
+	assert!(ctx.google.permissions().iter().all(|permission| permission.file_id != folder_id));
+
+	assert!(token_store::load_tokens(TEST_EMAIL).await.unwrap().is_none());
+	assert!(list_accounts().await.unwrap().accounts.is_empty());
+}
+
+#[tokio::test]
+async fn logout_of_an_unknown_account_is_an_error() {
+	let ctx = TestContext::new().await;
+	ctx.sign_in(valid_tokens()).await;
+
+	let error = logout(Some("outra@example.com".to_string()), false, &ctx.credentials, &ctx.http).await.unwrap_err();
+	assert_eq!(error.code(), "not_found");
+
+	// A conta conectada continua intacta
+	assert!(ctx.google.revoked_tokens().is_empty());
+	assert_eq!(list_accounts().await.unwrap().active.as_deref(), Some(TEST_EMAIL));
+	assert!(token_store::load_tokens(TEST_EMAIL).await.unwrap().is_some());
+}
+
+#[tokio::test]
+async fn logout_reports_local_cleanup_failures_after_revoking() {
+	let ctx = TestContext::new().await;
+	ctx.sign_in(valid_tokens()).await;
+
+	// Um app_config.json ilegível faz a remoção da pasta da conta falhar
+	let config_file = config_dir().unwrap().join("app_config.json");
+	let _ = std::fs::remove_file(&config_file);
+	std::fs::create_dir(&config_file).unwrap();
+
+	let report = logout(None, false, &ctx.credentials, &ctx.http).await.unwrap();
+	assert!(report.tokens_revoked);
+	assert_eq!(report.failures.len(), 1);
+	assert_eq!(report.failures[0].code(), "io");
+	assert_eq!(ctx.google.revoked_tokens(), vec![VALID_ACCESS_TOKEN, VALID_REFRESH_TOKEN]);
+	assert!(token_store::load_tokens(TEST_EMAIL).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn login_without_drive_access_is_rejected() {
+	let ctx = TestContext::new().await;
+	ctx.google.grant_scope("openid email");
+
+	start_device_authorization(&ctx.credentials, &ctx.http).await.unwrap();
+	let error = complete_device_authorization(&ctx.credentials, &ctx.http).await.unwrap_err();
+	assert_eq!(error.code(), "permission_denied");
+	assert_eq!(error.reason(), Some("insufficient_scope"));
+
+	assert!(list_accounts().await.unwrap().accounts.is_empty());
+}
+
+#[tokio::test]
+async fn login_records_broader_drive_scopes() {
+	let ctx = TestContext::new().await;
+	ctx.google.grant_scope("openid email https://www.googleapis.com/auth/drive https://www.googleapis.com/auth/drive.file");
+
+	start_device_authorization(&ctx.credentials, &ctx.http).await.unwrap();
+	let account = complete_device_authorization(&ctx.credentials, &ctx.http).await.unwrap();
+	assert_eq!(account.broader_scopes, vec!["https://www.googleapis.com/auth/drive"]);
+
+	let registry = list_accounts().await.unwrap();
+	assert_eq!(registry.accounts[0].broader_scopes, account.broader_scopes);
+}
+
+#[tokio::test]
+async fn legacy_config_dir_is_moved_and_tokens_migrated() {
+	let ctx = TestContext::new().await;
+	let legacy_dir = std::env::temp_dir().join(format!("cloudtray-legacy-{}", std::process::id()));
+	std::fs::create_dir_all(&legacy_dir).unwrap();
+	std::fs::write(legacy_dir.join("app_config.json"), r#"{ "retention_hours": 48 }"#).unwrap();
+	std::fs::write(legacy_dir.join("google_tokens.json"), serde_json::to_string(&valid_tokens()).unwrap()).unwrap();
+
+	migrate_legacy_files(&legacy_dir, &config_dir().unwrap()).await.unwrap();
+	assert_eq!(load_or_create_config().await.unwrap().retention_hours, 48);
+
+	let tokens = get_tokens(None, &ctx.credentials, &ctx.http).await.unwrap();
+	assert_eq!(tokens.access_token, VALID_ACCESS_TOKEN);
+	assert_eq!(list_accounts().await.unwrap().active.as_deref(), Some(TEST_EMAIL));
+
+	// Nenhum token em texto puro fica para trás, nem na pasta antiga nem na nova
+	assert!(!legacy_dir.join("google_tokens.json").exists());
+	assert!(!legacy_dir.join("app_config.json").exists());
+	assert!(!config_dir().unwrap().join("google_tokens.json").exists());
+	let _ = std::fs::remove_dir_all(&legacy_dir);
+}
+
+#[tokio::test]
+async fn legacy_tokens_are_migrated_and_removed() {
+	let ctx = TestContext::new().await;
+	let legacy_file = config_dir().unwrap().join("google_tokens.json");
+	std::fs::write(&legacy_file, serde_json::to_string(&valid_tokens()).unwrap()).unwrap();
+
+	let tokens = get_tokens(None, &ctx.credentials, &ctx.http).await.unwrap();
+	assert_eq!(tokens.access_token, VALID_ACCESS_TOKEN);
+
+	let registry = list_accounts().await.unwrap();
+	assert_eq!(registry.active.as_deref(), Some(TEST_EMAIL));
+	assert!(token_store::load_tokens(TEST_EMAIL).await.unwrap().is_some());
+	assert!(!legacy_file.exists());
+}
+
+#[tokio::test]
+async fn logout_never_trashes_a_folder_picked_by_the_user() {
+	let ctx = TestContext::new().await;
+	ctx.sign_in(valid_tokens()).await;
+	let picked_id = ctx.google.add_folder("Projetos");
+	select_drive_folder(picked_id.clone(), None, &ctx.credentials, &ctx.http).await.unwrap();
+
+	let report = logout(None, true, &ctx.credentials, &ctx.http).await.unwrap();
+	assert!(!report.folder_trashed);
+	assert!(report.tokens_revoked);
+	assert!(!ctx.google.file(&picked_id).unwrap().trashed);
+
+	// O relatório explica por que a pasta ficou
+	assert_eq!(report.failures.len(), 1);
+	assert_eq!(report.failures[0].code(), "invalid_input");
+}
+
+#[tokio::test]
+async fn logout_trashes_an_untagged_folder_from_an_older_version() {
+	let ctx = TestContext::new().await;
+	ctx.sign_in(valid_tokens()).await;
+	// Versões antigas criavam a pasta sem `appProperties` e não guardavam o id
+	let folder_id = ctx.google.add_folder("CloudTray");
+
+	let report = logout(None, true, &ctx.credentials, &ctx.http).await.unwrap();
+	assert!(report.folder_trashed);
+	assert!(report.failures.is_empty());
+	assert!(ctx.google.file(&folder_id).unwrap().trashed);
+}
@@ -0,0 +1,70 @@
+use std::process::ExitCode;
+
+use serde_json::json;
+
+use crate::cli::{config_value, error_json, set_config_key, upload_exit_code, UploadResult};
+use crate::config::AppConfig;
+use crate::drive::DriveFile;
+use crate::error::CloudTrayError;
+
+fn uploaded(name: &str) -> DriveFile {
+	DriveFile {
+		id: format!("id-{}", name),
+		name: name.to_string(),
+		web_view_link: format!("https://example.com/{}", name),
+	}
+}
+
+#[test]
+fn upload_report_lists_each_file_and_fails_when_any_upload_fails() {
+	let results = vec![
+		UploadResult::new("/tmp/a.png".to_string(), Ok(uploaded("a.png"))),
+		UploadResult::new("/tmp/b.png".to_string(), Err(CloudTrayError::not_found("Arquivo não encontrado"))),
+	];
+
+	let report = serde_json::to_value(&results).unwrap();
+	assert_eq!(report[0]["path"], "/tmp/a.png");
+	assert_eq!(report[0]["file"]["webViewLink"], "https://example.com/a.png");
+	assert!(report[0].get("error").is_none());
+	assert_eq!(report[1]["error"]["code"], "not_found");
+	assert!(report[1].get("file").is_none());
+
+	assert_eq!(upload_exit_code(&results), ExitCode::FAILURE);
+	assert_eq!(upload_exit_code(&results[..1]), ExitCode::SUCCESS);
+}
+
+#[test]
+fn json_errors_carry_the_error_code() {
+	let output = error_json(&CloudTrayError::config("Credenciais do Google não configuradas"));
+	assert_eq!(output["error"]["code"], "config");
+	assert_eq!(output["error"]["message"], "Credenciais do Google não configuradas");
+}
+
+#[test]
+fn config_set_parses_json_and_nested_keys() {
+	let config = AppConfig::default();
+
+	let updated = set_config_key(&config, "retention_hours", "48").unwrap();
+	assert_eq!(updated.retention_hours, 48);
+
+	let updated = set_config_key(&config, "network.proxy_url", "http://proxy:8080").unwrap();
+	let value = serde_json::to_value(&updated).unwrap();
+	assert_eq!(config_value(&value, "network.proxy_url"), Some(&json!("http://proxy:8080")));
+}
+
+#[test]
+fn config_set_rejects_unknown_keys_and_drive_folders() {
+	let config = AppConfig::default();
+
+	let error = set_config_key(&config, "retention_hour", "48").unwrap_err();
+	assert_eq!(error.code(), "invalid_input");
+
+	let error = set_config_key(&config, "retention_hours", "muitas").unwrap_err();
+	assert_eq!(error.code(), "invalid_input");
+
+	for key in ["drive_folders", "drive_folders.user@example.com"] {
+		let error = set_config_key(&config, key, "\"pasta\"").unwrap_err();
+		assert_eq!(error.code(), "invalid_input");
+		assert!(error.message().contains("folder set"));
+	}
+}
@@ -0,0 +1,164 @@
+use std::sync::{Arc, Mutex};
+
+use chrono::{Duration, Utc};
+use hyper::StatusCode;
+
+use super::fake_google::{FOLDER_MIME_TYPE, TEST_EMAIL};
+use super::{valid_tokens, TestContext};
+use crate::drive::GoogleDriveProvider;
+use crate::progress::UploadProgress;
+use crate::config::load_or_create_config;
+use crate::diagnostics::diagnostics;
+use crate::storage::{delete_old_files, get_or_create_app_folder, list_drive_folders, select_drive_folder, upload_file};
+
+#[tokio::test]
+async fn get_or_create_app_folder_creates_shared_folder_once() {
+	let ctx = TestContext::new().await;
+	ctx.sign_in(valid_tokens()).await;
+
+	let folder = get_or_create_app_folder(None, &ctx.credentials, &ctx.http).await.unwrap();
+	assert_eq!(folder.name, "CloudTray");
+
+	let created = ctx.google.file(&folder.id).expect("pasta criada no Drive");
+	assert_eq!(created.mime_type, FOLDER_MIME_TYPE);
+
+	let permissions = ctx.google.permissions();
+	assert_eq!(permissions.len(), 1);
+	assert_eq!(permissions[0].file_id, folder.id);
+	assert_eq!(permissions[0].kind, "anyone");
+	assert_eq!(permissions[0].role, "reader");
+
+	// Na segunda chamada a pasta existente é reaproveitada
+	let again = get_or_create_app_folder(None, &ctx.credentials, &ctx.http).await.unwrap();
+	assert_eq!(again.id, folder.id);
+	assert_eq!(ctx.google.files().len(), 1);
+}
+
+#[tokio::test]
+async fn get_or_create_app_folder_tracks_folder_by_id() {
+	let ctx = TestContext::new().await;
+	ctx.sign_in(valid_tokens()).await;
+
+	let folder = get_or_create_app_folder(None, &ctx.credentials, &ctx.http).await.unwrap();
+	assert_eq!(load_or_create_config().await.unwrap().drive_folders.get(TEST_EMAIL), Some(&folder.id));
+
+	// Outra pasta com o mesmo nome não muda o destino
+	ctx.google.add_folder("CloudTray");
+	let again = get_or_create_app_folder(None, &ctx.credentials, &ctx.http).await.unwrap();
+	assert_eq!(again.id, folder.id);
+
+	// Pasta na lixeira deixa de ser usada
+	ctx.google.trash(&folder.id);
+	let replacement = get_or_create_app_folder(None, &ctx.credentials, &ctx.http).await.unwrap();
+	assert_ne!(replacement.id, folder.id);
+	assert_eq!(load_or_create_config().await.unwrap().drive_folders.get(TEST_EMAIL), Some(&replacement.id));
+}
+
+#[tokio::test]
+async fn select_drive_folder_changes_upload_destination() {
+	let ctx = TestContext::new().await;
+	ctx.sign_in(valid_tokens()).await;
+	get_or_create_app_folder(None, &ctx.credentials, &ctx.http).await.unwrap();
+	let shared_id = ctx.google.add_folder("Compartilhados");
+
+	let folders = list_drive_folders(None, &ctx.credentials, &ctx.http).await.unwrap();
+	assert!(folders.iter().any(|folder| folder.id == shared_id));
+
+	let link = format!("https://drive.google.com/drive/folders/{}?usp=sharing", shared_id);
+	let selected = select_drive_folder(link, None, &ctx.credentials, &ctx.http).await.unwrap();
+	assert_eq!(selected.name, "Compartilhados");
+
+	let destination = get_or_create_app_folder(None, &ctx.credentials, &ctx.http).await.unwrap();
+	assert_eq!(destination.id, shared_id);
+
+	// Arquivos comuns e ids desconhecidos não viram destino
+	let file_id = ctx.google.add_file("foto.png", "image/png", &[&shared_id], Vec::new(), Utc::now());
+	let error = select_drive_folder(file_id, None, &ctx.credentials, &ctx.http).await.unwrap_err();
+	assert_eq!(error.code(), "not_found");
+	let error = select_drive_folder("desconhecida".to_string(), None, &ctx.credentials, &ctx.http).await.unwrap_err();
+	assert_eq!(error.code(), "not_found");
+	assert_eq!(get_or_create_app_folder(None, &ctx.credentials, &ctx.http).await.unwrap().id, shared_id);
+}
+
+#[tokio::test]
+async fn list_drive_folders_follows_every_page() {
+	let ctx = TestContext::new().await;
+	ctx.sign_in(valid_tokens()).await;
+	for index in 0..150 {
+		ctx.google.add_folder(&format!("CloudTray {}", index));
+	}
+
+	let folders = list_drive_folders(None, &ctx.credentials, &ctx.http).await.unwrap();
+	assert_eq!(folders.len(), 150);
+}
+
+#[tokio::test]
+async fn upload_file_sends_multipart_and_shares_file() {
+	let ctx = TestContext::new().await;
+	ctx.sign_in(valid_tokens()).await;
+	let folder_id = ctx.google.add_folder("CloudTray");
+
+	let content = b"conteudo de teste".to_vec();
+	let reported = Arc::new(Mutex::new(Vec::new()));
+	let progress = Arc::clone(&reported);
+	let file = upload_file(
+		content.clone(),
+		"captura.png".to_string(),
+		folder_id.clone(),
+		None,
+		&ctx.credentials,
+		&ctx.http,
+		Box::new(move |update: UploadProgress| progress.lock().unwrap().push(update.progress)),
+	).await.unwrap();
+
+	assert_eq!(file.name, "captura.png");
+	assert!(file.web_view_link.contains(&file.id));
+
+	let uploaded = ctx.google.file(&file.id).expect("arquivo enviado ao Drive");
+	assert_eq!(uploaded.content, content);
+	assert_eq!(uploaded.mime_type, "image/png");
+	assert_eq!(uploaded.parents, vec![folder_id]);
+
+	assert!(ctx
+		.google
+		.permissions()
+		.iter()
+		.any(|permission| permission.file_id == file.id && permission.kind == "anyone" && permission.role == "reader"));
+
+	assert_eq!(reported.lock().unwrap().last(), Some(&100));
+}
+
+#[tokio::test]
+async fn transient_drive_errors_are_retried_and_reported() {
+	let ctx = TestContext::new().await;
+	ctx.sign_in(valid_tokens()).await;
+	let folder_id = ctx.google.add_folder("CloudTray");
+	let retries_before = diagnostics().retry_count;
+
+	ctx.google.fail_drive_requests(&[StatusCode::SERVICE_UNAVAILABLE]);
+	let folders = list_drive_folders(None, &ctx.credentials, &ctx.http).await.unwrap();
+	assert!(folders.iter().any(|folder| folder.id == folder_id));
+
+	let report = diagnostics();
+	assert_eq!(report.retry_count, retries_before + 1);
+	assert!(report.warnings.last().unwrap().message.contains("listar pastas"));
+}
+
+#[tokio::test]
+async fn delete_old_files_removes_only_expired_files() {
+	let ctx = TestContext::new().await;
+	ctx.sign_in(valid_tokens()).await;
+	let folder_id = ctx.google.add_folder("CloudTray");
+
+	// Retenção padrão: 24 horas
+	let expired = ctx.google.add_file("antigo.png", "image/png", &[&folder_id], Vec::new(), Utc::now() - Duration::hours(48));
+	let fresh = ctx.google.add_file("recente.png", "image/png", &[&folder_id], Vec::new(), Utc::now() - Duration::hours(1));
+	let elsewhere = ctx.google.add_file("outra-pasta.png", "image/png", &["other-folder"], Vec::new(), Utc::now() - Duration::hours(48));
+
+	let provider = GoogleDriveProvider::new(None, &ctx.credentials, &ctx.http);
+	delete_old_files(&provider, &folder_id).await.unwrap();
+
+	assert!(ctx.google.file(&expired).is_none());
+	assert!(ctx.google.file(&fresh).is_some());
+	assert!(ctx.google.file(&elsewhere).is_some());
+}
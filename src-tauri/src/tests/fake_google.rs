@@ -0,0 +1,578 @@
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use serde_json::{json, Value};
+use tokio::sync::oneshot;
+
+use crate::endpoints::GoogleEndpoints;
+
+pub const TEST_EMAIL: &str = "tester@example.com";
+pub const VALID_ACCESS_TOKEN: &str = "valid-access-token";
+pub const REFRESHED_ACCESS_TOKEN: &str = "refreshed-access-token";
+pub const VALID_REFRESH_TOKEN: &str = "valid-refresh-token";
+pub const DEVICE_CODE: &str = "fake-device-code";
+pub const USER_CODE: &str = "ABCD-EFGH";
+
+pub const DEFAULT_GRANTED_SCOPE: &str = "openid email https://www.googleapis.com/auth/drive.file";
+
+pub const FOLDER_MIME_TYPE: &str = "application/vnd.google-apps.folder";
+
+#[derive(Debug, Clone)]
+pub struct FakeFile {
+	pub id: String,
+	pub name: String,
+	pub mime_type: String,
+	pub parents: Vec<String>,
+	pub content: Vec<u8>,
+	pub modified_time: DateTime<Utc>,
+	pub trashed: bool,
+	pub app_properties: Value,
+}
+
+#[derive(Debug, Clone)]
+pub struct FakePermission {
+	pub id: String,
+	pub file_id: String,
+	pub role: String,
+	pub kind: String,
+}
+
+#[derive(Default)]
+struct FakeState {
+	files: Vec<FakeFile>,
+	permissions: Vec<FakePermission>,
+	token_requests: Vec<HashMap<String, String>>,
+	revoked_tokens: Vec<String>,
+	device_polls: u32,
+	device_slow_down: bool,
+	drive_failures: Vec<StatusCode>,
+	granted_scope: Option<String>,
+	next_id: u64,
+}
+
+impl FakeState {
+	fn new_id(&mut self) -> String {
+		self.next_id += 1;
+		format!("fake-file-{}", self.next_id)
+	}
+
+	fn new_permission_id(&mut self) -> String {
+		self.next_id += 1;
+		format!("permission-{}", self.next_id)
+	}
+}
+
+/// Servidor local que imita o token endpoint, o userinfo e a parte da API do Drive usada pelo app.
+/// Os dados ficam em memória e podem ser inspecionados pelos testes.
+pub struct FakeGoogle {
+	addr: SocketAddr,
+	state: Arc<Mutex<FakeState>>,
+	shutdown: Option<oneshot::Sender<()>>,
+}
+
+impl FakeGoogle {
+	pub fn start() -> Self {
+		let state = Arc::new(Mutex::new(FakeState::default()));
+
+		let service_state = Arc::clone(&state);
+		let make_service = make_service_fn(move |_| {
+			let state = Arc::clone(&service_state);
+			async move {
+				Ok::<_, Infallible>(service_fn(move |request| handle(Arc::clone(&state), request)))
+			}
+		});
+
+		let server = Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0))).serve(make_service);
+		let addr = server.local_addr();
+
+		let (shutdown, shutdown_signal) = oneshot::channel::<()>();
+		tokio::spawn(server.with_graceful_shutdown(async {
+			let _ = shutdown_signal.await;
+		}));
+
+		Self { addr, state, shutdown: Some(shutdown) }
+	}
+
+	pub fn endpoints(&self) -> GoogleEndpoints {
+		let base = format!("http://{}", self.addr);
+		GoogleEndpoints {
+			auth_url: format!("{}/auth", base),
+			token_url: format!("{}/token", base),
+			device_code_url: format!("{}/device/code", base),
+			revoke_url: format!("{}/revoke", base),
+			userinfo_url: format!("{}/userinfo", base),
+			drive_api_url: format!("{}/drive/v3", base),
+			drive_upload_url: format!("{}/upload/drive/v3", base),
+		}
+	}
+
+	pub fn add_folder(&self, name: &str) -> String {
+		self.add_file(name, FOLDER_MIME_TYPE, &[], Vec::new(), Utc::now())
+	}
+
+	pub fn add_file(&self, name: &str, mime_type: &str, parents: &[&str], content: Vec<u8>, modified_time: DateTime<Utc>) -> String {
+		let mut state = self.state.lock().unwrap();
+		let id = state.new_id();
+		state.files.push(FakeFile {
+			id: id.clone(),
+			name: name.to_string(),
+			mime_type: mime_type.to_string(),
+			parents: parents.iter().map(|parent| parent.to_string()).collect(),
+			content,
+			modified_time,
+			trashed: false,
+			app_properties: json!({}),
+		});
+		id
+	}
+
+	pub fn files(&self) -> Vec<FakeFile> {
+		self.state.lock().unwrap().files.clone()
+	}
+
+	pub fn file(&self, id: &str) -> Option<FakeFile> {
+		self.files().into_iter().find(|file| file.id == id)
+	}
+
+	pub fn permissions(&self) -> Vec<FakePermission> {
+		self.state.lock().unwrap().permissions.clone()
+	}
+
+	/// Formulários recebidos pelo token endpoint, na ordem em que chegaram.
+	pub fn token_requests(&self) -> Vec<HashMap<String, String>> {
+		self.state.lock().unwrap().token_requests.clone()
+	}
+
+	/// Escopos devolvidos pelo token endpoint nas próximas respostas, como se o usuário tivesse marcado outras permissões.
+	pub fn grant_scope(&self, scope: &str) {
+		self.state.lock().unwrap().granted_scope = Some(scope.to_string());
+	}
+
+	pub fn trash(&self, id: &str) {
+		let mut state = self.state.lock().unwrap();
+		if let Some(file) = state.files.iter_mut().find(|file| file.id == id) {
+			file.trashed = true;
+		}
+	}
+
+	/// A consulta pendente do fluxo de dispositivo responde `slow_down` em vez de `authorization_pending`.
+	pub fn slow_down_device_polls(&self) {
+		self.state.lock().unwrap().device_slow_down = true;
+	}
+
+	/// As próximas requisições à API do Drive recebem estes erros, na ordem, antes de voltar ao normal.
+	pub fn fail_drive_requests(&self, statuses: &[StatusCode]) {
+		self.state.lock().unwrap().drive_failures.extend_from_slice(statuses);
+	}
+
+	/// Tokens recebidos pelo endpoint de revogação.
+	pub fn revoked_tokens(&self) -> Vec<String> {
+		self.state.lock().unwrap().revoked_tokens.clone()
+	}
+}
+
+impl Drop for FakeGoogle {
+	fn drop(&mut self) {
+		if let Some(shutdown) = self.shutdown.take() {
+			let _ = shutdown.send(());
+		}
+	}
+}
+
+async fn handle(state: Arc<Mutex<FakeState>>, request: Request<Body>) -> Result<Response<Body>, Infallible> {
+	let method = request.method().clone();
+	let path = request.uri().path().to_string();
+	let query = parse_pairs(request.uri().query().unwrap_or_default().as_bytes());
+	let authorization = request
+		.headers()
+		.get(hyper::header::AUTHORIZATION)
+		.and_then(|value| value.to_str().ok())
+		.map(|value| value.to_string());
+	let content_type = request
+		.headers()
+		.get(hyper::header::CONTENT_TYPE)
+		.and_then(|value| value.to_str().ok())
+		.unwrap_or_default()
+		.to_string();
+	let body = hyper::body::to_bytes(request.into_body()).await.unwrap_or_default();
+
+	if method == Method::POST && path == "/token" {
+		return Ok(token(&state, parse_pairs(&body)));
+	}
+
+	if method == Method::POST && path == "/revoke" {
+		let token = parse_pairs(&body).remove("token").unwrap_or_default();
+		state.lock().unwrap().revoked_tokens.push(token);
+		return Ok(json_response(StatusCode::OK, json!({})));
+	}
+
+	if method == Method::POST && path == "/device/code" {
+		return Ok(json_response(StatusCode::OK, json!({
+			"device_code": DEVICE_CODE,
+			"user_code": USER_CODE,
+			"verification_url": "https://www.google.com/device",
+			"expires_in": 60,
+			"interval": 0,
+		})));
+	}
+
+	if !is_authorized(authorization.as_deref()) {
+		return Ok(google_error(StatusCode::UNAUTHORIZED, "Invalid Credentials", "authError"));
+	}
+
+	let segments: Vec<&str> = path.trim_start_matches('/').split('/').collect();
+	if segments.contains(&"drive") {
+		let mut state = state.lock().unwrap();
+		if !state.drive_failures.is_empty() {
+			let status = state.drive_failures.remove(0);
+			return Ok(google_error(status, "Backend Error", "backendError"));
+		}
+	}
+
+	let response = match (&method, segments.as_slice()) {
+		(&Method::GET, ["userinfo"]) => json_response(StatusCode::OK, json!({
+			"email": TEST_EMAIL,
+			"name": "Test User",
+		})),
+		(&Method::GET, ["drive", "v3", "about"]) => json_response(StatusCode::OK, json!({
+			"user": { "emailAddress": TEST_EMAIL, "displayName": "Test User" },
+		})),
+		(&Method::GET, ["drive", "v3", "files"]) => list_files(&state, &query),
+		(&Method::POST, ["drive", "v3", "files"]) => create_file(&state, &body),
+		(&Method::GET, ["drive", "v3", "files", file_id]) => get_file(&state, file_id),
+		(&Method::PATCH, ["drive", "v3", "files", file_id]) => update_file(&state, file_id, &body),
+		(&Method::DELETE, ["drive", "v3", "files", file_id]) => delete_file(&state, file_id),
+		(&Method::GET, ["drive", "v3", "files", file_id, "permissions"]) => list_permissions(&state, file_id),
+		(&Method::POST, ["drive", "v3", "files", file_id, "permissions"]) => create_permission(&state, file_id, &body),
+		(&Method::DELETE, ["drive", "v3", "files", file_id, "permissions", permission_id]) => {
+			delete_permission(&state, file_id, permission_id)
+		}
+		(&Method::POST, ["upload", "drive", "v3", "files"]) if query.get("uploadType").map(String::as_str) == Some("multipart") => {
+			upload_multipart(&state, &content_type, &body)
+		}
+		_ => google_error(StatusCode::NOT_FOUND, "Not Found", "notFound"),
+	};
+
+	Ok(response)
+}
+
+fn parse_pairs(input: &[u8]) -> HashMap<String, String> {
+	url::form_urlencoded::parse(input).into_owned().collect()
+}
+
+fn is_authorized(authorization: Option<&str>) -> bool {
+	matches!(
+		authorization.and_then(|value| value.strip_prefix("Bearer ")),
+		Some(VALID_ACCESS_TOKEN) | Some(REFRESHED_ACCESS_TOKEN)
+	)
+}
+
+fn json_response(status: StatusCode, body: Value) -> Response<Body> {
+	Response::builder()
+		.status(status)
+		.header(hyper::header::CONTENT_TYPE, "application/json")
+		.body(Body::from(body.to_string()))
+		.unwrap()
+}
+
+fn google_error(status: StatusCode, message: &str, reason: &str) -> Response<Body> {
+	json_response(status, json!({
+		"error": {
+			"code": status.as_u16(),
+			"message": message,
+			"errors": [{ "reason": reason, "message": message }],
+		}
+	}))
+}
+
+fn file_json(file: &FakeFile) -> Value {
+	json!({
+		"id": file.id,
+		"name": file.name,
+		"mimeType": file.mime_type,
+		"trashed": file.trashed,
+		"appProperties": file.app_properties,
+		"webViewLink": format!("https://drive.example.com/file/d/{}/view", file.id),
+	})
+}
+
+fn token(state: &Mutex<FakeState>, form: HashMap<String, String>) -> Response<Body> {
+	let grant_type = form.get("grant_type").cloned().unwrap_or_default();
+	let refresh_token = form.get("refresh_token").cloned().unwrap_or_default();
+	let device_code = form.get("device_code").cloned().unwrap_or_default();
+	let mut state = state.lock().unwrap();
+	state.token_requests.push(form);
+	let granted_scope = state.granted_scope.clone().unwrap_or_else(|| DEFAULT_GRANTED_SCOPE.to_string());
+
+	// O fluxo de dispositivo fica pendente na primeira consulta, como se o usuário ainda não tivesse aprovado
+	if grant_type == "urn:ietf:params:oauth:grant-type:device_code" && device_code == DEVICE_CODE {
+		state.device_polls += 1;
+		if state.device_polls == 1 && state.device_slow_down {
+			return json_response(StatusCode::BAD_REQUEST, json!({
+				"error": "slow_down",
+				"error_description": "Forbidden",
+			}));
+		}
+		if state.device_polls == 1 {
+			return json_response(StatusCode::PRECONDITION_REQUIRED, json!({
+				"error": "authorization_pending",
+				"error_description": "Precondition Required",
+			}));
+		}
+
+		return json_response(StatusCode::OK, json!({
+			"access_token": VALID_ACCESS_TOKEN,
+			"refresh_token": VALID_REFRESH_TOKEN,
+			"expires_in": 3599,
+			"scope": granted_scope,
+			"token_type": "Bearer",
+		}));
+	}
+
+	if grant_type == "refresh_token" && refresh_token == VALID_REFRESH_TOKEN {
+		return json_response(StatusCode::OK, json!({
+			"access_token": REFRESHED_ACCESS_TOKEN,
+			"expires_in": 3599,
+			"scope": granted_scope,
+			"token_type": "Bearer",
+		}));
+	}
+
+	json_response(StatusCode::BAD_REQUEST, json!({
+		"error": "invalid_grant",
+		"error_description": "Token has been expired or revoked.",
+	}))
+}
+
+/// Entende só as cláusulas que o app monta: `'id' in parents`, `name = '...'`, `mimeType = '...'`,
+/// `modifiedTime < '...'` e `trashed = false`.
+fn matches_query(file: &FakeFile, query: &str) -> bool {
+	query.split(" and ").all(|clause| {
+		let clause = clause.trim();
+		let quoted = clause.split('\'').nth(1).unwrap_or_default();
+
+		if clause.ends_with("in parents") {
+			file.parents.iter().any(|parent| parent == quoted)
+		} else if clause.starts_with("name =") {
+			file.name == quoted
+		} else if clause.starts_with("mimeType =") {
+			file.mime_type == quoted
+		} else if clause.starts_with("modifiedTime <") {
+			DateTime::parse_from_rfc3339(quoted).is_ok_and(|limit| file.modified_time < limit)
+		} else {
+			clause == "trashed = false" && !file.trashed
+		}
+	})
+}
+
+fn list_files(state: &Mutex<FakeState>, query: &HashMap<String, String>) -> Response<Body> {
+	let state = state.lock().unwrap();
+	let filter = query.get("q").cloned().unwrap_or_default();
+
+	let mut files: Vec<&FakeFile> = state
+		.files
+		.iter()
+		.filter(|file| filter.is_empty() || matches_query(file, &filter))
+		.collect();
+	files.sort_by_key(|file| std::cmp::Reverse(file.modified_time));
+
+	// O token de página é só a posição do próximo item
+	let page_size = query.get("pageSize").and_then(|size| size.parse().ok()).unwrap_or(100);
+	let start = query.get("pageToken").and_then(|token| token.parse().ok()).unwrap_or(0);
+	let end = files.len().min(start + page_size);
+
+	let mut body = json!({
+		"files": files[start.min(end)..end].iter().copied().map(file_json).collect::<Vec<_>>(),
+	});
+	if end < files.len() {
+		body["nextPageToken"] = json!(end.to_string());
+	}
+	json_response(StatusCode::OK, body)
+}
+
+fn parents_from(metadata: &Value) -> Vec<String> {
+	metadata["parents"]
+		.as_array()
+		.map(|parents| parents.iter().filter_map(|parent| parent.as_str().map(String::from)).collect())
+		.unwrap_or_default()
+}
+
+fn create_file(state: &Mutex<FakeState>, body: &[u8]) -> Response<Body> {
+	let Ok(metadata) = serde_json::from_slice::<Value>(body) else {
+		return google_error(StatusCode::BAD_REQUEST, "Invalid JSON payload", "parseError");
+	};
+
+	let mut state = state.lock().unwrap();
+	let file = FakeFile {
+		id: state.new_id(),
+		name: metadata["name"].as_str().unwrap_or("Untitled").to_string(),
+		mime_type: metadata["mimeType"].as_str().unwrap_or("application/octet-stream").to_string(),
+		parents: parents_from(&metadata),
+		content: Vec::new(),
+		modified_time: Utc::now(),
+		trashed: false,
+		app_properties: metadata["appProperties"].clone(),
+	};
+	let response = json_response(StatusCode::OK, file_json(&file));
+	state.files.push(file);
+	response
+}
+
+fn get_file(state: &Mutex<FakeState>, file_id: &str) -> Response<Body> {
+	let state = state.lock().unwrap();
+	match state.files.iter().find(|file| file.id == file_id) {
+		Some(file) => json_response(StatusCode::OK, file_json(file)),
+		None => google_error(StatusCode::NOT_FOUND, &format!("File not found: {}.", file_id), "notFound"),
+	}
+}
+
+/// Só o campo `trashed` é alterado pelo app.
+fn update_file(state: &Mutex<FakeState>, file_id: &str, body: &[u8]) -> Response<Body> {
+	let Ok(metadata) = serde_json::from_slice::<Value>(body) else {
+		return google_error(StatusCode::BAD_REQUEST, "Invalid JSON payload", "parseError");
+	};
+
+	let mut state = state.lock().unwrap();
+	let Some(file) = state.files.iter_mut().find(|file| file.id == file_id) else {
+		return google_error(StatusCode::NOT_FOUND, &format!("File not found: {}.", file_id), "notFound");
+	};
+
+	if let Some(trashed) = metadata["trashed"].as_bool() {
+		file.trashed = trashed;
+	}
+	if let Some(app_properties) = metadata["appProperties"].as_object() {
+		for (key, value) in app_properties {
+			file.app_properties[key] = value.clone();
+		}
+	}
+	json_response(StatusCode::OK, file_json(file))
+}
+
+fn delete_file(state: &Mutex<FakeState>, file_id: &str) -> Response<Body> {
+	let mut state = state.lock().unwrap();
+	let Some(index) = state.files.iter().position(|file| file.id == file_id) else {
+		return google_error(StatusCode::NOT_FOUND, &format!("File not found: {}.", file_id), "notFound");
+	};
+
+	state.files.remove(index);
+	Response::builder().status(StatusCode::NO_CONTENT).body(Body::empty()).unwrap()
+}
+
+fn create_permission(state: &Mutex<FakeState>, file_id: &str, body: &[u8]) -> Response<Body> {
+	let mut state = state.lock().unwrap();
+	if !state.files.iter().any(|file| file.id == file_id) {
+		return google_error(StatusCode::NOT_FOUND, &format!("File not found: {}.", file_id), "notFound");
+	}
+
+	let permission: Value = serde_json::from_slice(body).unwrap_or_default();
+	let permission = FakePermission {
+		id: state.new_permission_id(),
+		file_id: file_id.to_string(),
+		role: permission["role"].as_str().unwrap_or_default().to_string(),
+		kind: permission["type"].as_str().unwrap_or_default().to_string(),
+	};
+	let response = json_response(StatusCode::OK, json!({
+		"id": permission.id,
+		"role": permission.role,
+		"type": permission.kind,
+	}));
+	state.permissions.push(permission);
+	response
+}
+
+fn list_permissions(state: &Mutex<FakeState>, file_id: &str) -> Response<Body> {
+	let state = state.lock().unwrap();
+	let permissions: Vec<Value> = state
+		.permissions
+		.iter()
+		.filter(|permission| permission.file_id == file_id)
+		.map(|permission| json!({ "id": permission.id, "role": permission.role, "type": permission.kind }))
+		.collect();
+
+	json_response(StatusCode::OK, json!({ "permissions": permissions }))
+}
+
+fn delete_permission(state: &Mutex<FakeState>, file_id: &str, permission_id: &str) -> Response<Body> {
+	let mut state = state.lock().unwrap();
+	let Some(index) = state
+		.permissions
+		.iter()
+		.position(|permission| permission.file_id == file_id && permission.id == permission_id)
+	else {
+		return google_error(StatusCode::NOT_FOUND, &format!("Permission not found: {}.", permission_id), "notFound");
+	};
+
+	state.permissions.remove(index);
+	Response::builder().status(StatusCode::NO_CONTENT).body(Body::empty()).unwrap()
+}
+
+fn split_bytes<'a>(input: &'a [u8], separator: &[u8]) -> Vec<&'a [u8]> {
+	let mut parts = Vec::new();
+	let mut start = 0;
+	let mut index = 0;
+
+	while index + separator.len() <= input.len() {
+		if &input[index..index + separator.len()] == separator {
+			parts.push(&input[start..index]);
+			index += separator.len();
+			start = index;
+		} else {
+			index += 1;
+		}
+	}
+
+	parts.push(&input[start..]);
+	parts
+}
+
+/// Corpo `multipart/related`: primeiro os metadados em JSON, depois o conteúdo do arquivo.
+fn upload_multipart(state: &Mutex<FakeState>, content_type: &str, body: &[u8]) -> Response<Body> {
+	let Some(boundary) = content_type.split("boundary=").nth(1) else {
+		return google_error(StatusCode::BAD_REQUEST, "Missing multipart boundary", "badContent");
+	};
+
+	let delimiter = format!("--{}", boundary.trim_matches('"'));
+	let parts: Vec<(&[u8], &[u8])> = split_bytes(body, delimiter.as_bytes())
+		.into_iter()
+		.filter_map(|part| {
+			let sections = split_bytes(part, b"\r\n\r\n");
+			let headers = sections.first()?;
+			let header_len = headers.len() + 4;
+			let content = part.get(header_len..)?;
+			Some((*headers, content.strip_suffix(b"\r\n").unwrap_or(content)))
+		})
+		.collect();
+
+	let [(_, metadata), (media_headers, content)] = parts.as_slice() else {
+		return google_error(StatusCode::BAD_REQUEST, "Expected metadata and media parts", "badContent");
+	};
+	let Ok(metadata) = serde_json::from_slice::<Value>(metadata) else {
+		return google_error(StatusCode::BAD_REQUEST, "Invalid metadata part", "parseError");
+	};
+
+	let media_type = String::from_utf8_lossy(media_headers)
+		.lines()
+		.find_map(|line| line.split_once(':').filter(|(name, _)| name.eq_ignore_ascii_case("content-type")))
+		.map(|(_, value)| value.trim().to_string())
+		.unwrap_or_else(|| "application/octet-stream".to_string());
+
+	let mut state = state.lock().unwrap();
+	let file = FakeFile {
+		id: state.new_id(),
+		name: metadata["name"].as_str().unwrap_or("Untitled").to_string(),
+		mime_type: media_type,
+		parents: parents_from(&metadata),
+		content: content.to_vec(),
+		modified_time: Utc::now(),
+		trashed: false,
+		app_properties: metadata["appProperties"].clone(),
+	};
+	let response = json_response(StatusCode::OK, file_json(&file));
+	state.files.push(file);
+	response
+}
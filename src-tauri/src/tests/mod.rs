@@ -0,0 +1,95 @@
+//! Testes de integração que rodam o núcleo contra o `FakeGoogle`, sem acesso à rede nem ao keyring.
+//! Cada teste usa um perfil isolado (`CLOUDTRAY_CONFIG_DIR`) numa pasta temporária.
+
+mod auth;
+mod cli;
+mod drive;
+mod fake_google;
+mod queue;
+mod s3;
+mod token_store;
+mod webdav;
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use chrono::Utc;
+use tokio::sync::MutexGuard;
+
+use crate::accounts::{register_account, Account};
+use crate::config::CONFIG_DIR_ENV;
+use crate::drive::GoogleTokens;
+use crate::http_client::HttpClient;
+use crate::GoogleCredentials;
+
+use self::fake_google::{FakeGoogle, DEFAULT_GRANTED_SCOPE, TEST_EMAIL, VALID_ACCESS_TOKEN, VALID_REFRESH_TOKEN};
+
+// A pasta de configuração vem de uma variável de ambiente do processo, então os testes rodam um de cada vez
+static TEST_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+static NEXT_PROFILE: AtomicU64 = AtomicU64::new(0);
+
+pub struct TestContext {
+	pub google: FakeGoogle,
+	pub credentials: GoogleCredentials,
+	pub http: HttpClient,
+	config_dir: PathBuf,
+	_guard: MutexGuard<'static, ()>,
+}
+
+impl TestContext {
+	pub async fn new() -> Self {
+		let guard = TEST_LOCK.lock().await;
+
+		let config_dir = std::env::temp_dir().join(format!(
+			"cloudtray-test-{}-{}",
+			std::process::id(),
+			NEXT_PROFILE.fetch_add(1, Ordering::Relaxed)
+		));
+		std::fs::create_dir_all(&config_dir).unwrap();
+		std::env::set_var(CONFIG_DIR_ENV, &config_dir);
+
+		let google = FakeGoogle::start();
+
+		let credentials = GoogleCredentials::new("test-client".to_string(), "test-secret".to_string(), google.endpoints());
+		let http = HttpClient::new().unwrap();
+
+		Self { google, credentials, http, config_dir, _guard: guard }
+	}
+
+	/// Registra a conta de teste como ativa, como se o login já tivesse acontecido.
+	pub async fn sign_in(&self, tokens: GoogleTokens) {
+		let account = Account {
+			email: TEST_EMAIL.to_string(),
+			name: Some("Test User".to_string()),
+			picture: None,
+			broader_scopes: Vec::new(),
+		};
+		register_account(account, &tokens).await.unwrap();
+	}
+}
+
+impl Drop for TestContext {
+	fn drop(&mut self) {
+		std::env::remove_var(CONFIG_DIR_ENV);
+		let _ = std::fs::remove_dir_all(&self.config_dir);
+	}
+}
+
+pub fn valid_tokens() -> GoogleTokens {
+	GoogleTokens {
+		access_token: VALID_ACCESS_TOKEN.to_string(),
+		refresh_token: VALID_REFRESH_TOKEN.to_string(),
+		expires_in: 3599,
+		token_type: "Bearer".to_string(),
+		expires_at: Utc::now().timestamp() + 3599,
+		scope: Some(DEFAULT_GRANTED_SCOPE.to_string()),
+	}
+}
+
+pub fn expired_tokens() -> GoogleTokens {
+	GoogleTokens {
+		access_token: "expired-access-token".to_string(),
+		expires_at: Utc::now().timestamp() - 60,
+		..valid_tokens()
+	}
+}
@@ -0,0 +1,139 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::sync::Notify;
+
+use crate::drive::DriveFile;
+use crate::error::CloudTrayError;
+use crate::queue::{JobState, UploadJob, UploadQueue};
+
+fn uploaded(job: &UploadJob) -> DriveFile {
+	DriveFile {
+		id: format!("file-{}", job.id),
+		name: job.file_name.clone(),
+		web_view_link: format!("https://example.com/{}", job.id),
+	}
+}
+
+/// Fila com um worker que devolve os resultados de `results` em ordem e registra os eventos emitidos.
+fn queue_with_results(results: Vec<Result<(), CloudTrayError>>) -> (UploadQueue, Arc<AtomicU32>, Arc<Mutex<Vec<UploadJob>>>) {
+	let calls = Arc::new(AtomicU32::new(0));
+	let events = Arc::new(Mutex::new(Vec::new()));
+	let results = Arc::new(Mutex::new(results));
+
+	let worker_calls = Arc::clone(&calls);
+	let emitted = Arc::clone(&events);
+	let queue = UploadQueue::new(
+		Box::new(move |job: UploadJob| {
+			worker_calls.fetch_add(1, Ordering::SeqCst);
+			let result = results.lock().unwrap().remove(0).map(|_| uploaded(&job));
+			Box::pin(async move { result })
+		}),
+		Box::new(move |job: UploadJob| emitted.lock().unwrap().push(job)),
+	);
+
+	(queue, calls, events)
+}
+
+async fn wait_until(queue: &UploadQueue, done: impl Fn(&[UploadJob]) -> bool) -> Vec<UploadJob> {
+	for _ in 0..200 {
+		let jobs = queue.list();
+		if done(&jobs) {
+			return jobs;
+		}
+		tokio::time::sleep(Duration::from_millis(25)).await;
+	}
+	panic!("estado esperado não alcançado: {:?}", queue.list());
+}
+
+async fn wait_finished(queue: &UploadQueue) -> Vec<UploadJob> {
+	wait_until(queue, |jobs| jobs.iter().all(|job| job.state.is_finished())).await
+}
+
+#[tokio::test]
+async fn transient_errors_are_retried() {
+	let (queue, calls, events) = queue_with_results(vec![Err(CloudTrayError::network("conexão recusada")), Ok(())]);
+
+	queue.enqueue(vec!["/tmp/captura.png".to_string()], "pasta".to_string(), "conta".to_string(), 2).unwrap();
+	let jobs = wait_finished(&queue).await;
+
+	assert_eq!(calls.load(Ordering::SeqCst), 2);
+	assert_eq!(jobs[0].state, JobState::Completed);
+	assert_eq!(jobs[0].attempts, 2);
+	assert_eq!(jobs[0].file.as_ref().unwrap().name, "captura.png");
+
+	let states: Vec<JobState> = events.lock().unwrap().iter().map(|job| job.state).collect();
+	assert_eq!(states, vec![
+		JobState::Queued,
+		JobState::Running,
+		JobState::Retrying,
+		JobState::Running,
+		JobState::Completed,
+	]);
+}
+
+#[tokio::test]
+async fn permanent_errors_fail_without_retrying() {
+	let (queue, calls, _events) = queue_with_results(vec![Err(CloudTrayError::not_found("Arquivo não encontrado"))]);
+
+	queue.enqueue(vec!["/tmp/sumiu.png".to_string()], "pasta".to_string(), "conta".to_string(), 2).unwrap();
+	let jobs = wait_finished(&queue).await;
+
+	assert_eq!(calls.load(Ordering::SeqCst), 1);
+	assert_eq!(jobs[0].state, JobState::Failed);
+	assert_eq!(jobs[0].error.as_ref().unwrap().code(), CloudTrayError::not_found("").code());
+}
+
+#[tokio::test]
+async fn jobs_respect_the_parallel_limit_and_can_be_cancelled() {
+	let release = Arc::new(Notify::new());
+	let worker_release = Arc::clone(&release);
+	let queue = UploadQueue::new(
+		Box::new(move |job: UploadJob| {
+			let release = Arc::clone(&worker_release);
+			Box::pin(async move {
+				release.notified().await;
+				Ok(uploaded(&job))
+			})
+		}),
+		Box::new(|_| {}),
+	);
+
+	let paths = ["/tmp/a.png", "/tmp/b.png", "/tmp/c.png"].map(String::from).to_vec();
+	let jobs = queue.enqueue(paths, "pasta".to_string(), "conta".to_string(), 1).unwrap();
+
+	let states = |queue: &UploadQueue| queue.list().iter().map(|job| job.state).collect::<Vec<_>>();
+	assert_eq!(states(&queue), vec![JobState::Running, JobState::Queued, JobState::Queued]);
+
+	// Cancelar o job em andamento libera a vaga para o próximo
+	queue.cancel(jobs[0].id).unwrap();
+	assert_eq!(states(&queue), vec![JobState::Cancelled, JobState::Running, JobState::Queued]);
+	assert!(queue.cancel(jobs[0].id).is_err());
+
+	queue.pause(jobs[2].id).unwrap();
+	release.notify_one();
+	let finished = wait_until(&queue, |jobs| jobs[1].state == JobState::Completed).await;
+	assert_eq!(finished[2].state, JobState::Paused);
+
+	queue.resume(jobs[2].id).unwrap();
+	release.notify_one();
+	let jobs = wait_finished(&queue).await;
+	assert_eq!(jobs[2].state, JobState::Completed);
+
+	queue.clear_finished();
+	assert!(queue.list().is_empty());
+}
+
+#[tokio::test]
+async fn an_invalid_path_queues_nothing() {
+	let (queue, calls, events) = queue_with_results(vec![Ok(())]);
+
+	let paths = vec!["/tmp/captura.png".to_string(), "/".to_string()];
+	let error = queue.enqueue(paths, "pasta".to_string(), "conta".to_string(), 2).unwrap_err();
+
+	assert_eq!(error.code(), "invalid_input");
+	assert!(queue.list().is_empty());
+	assert!(events.lock().unwrap().is_empty());
+	assert_eq!(calls.load(Ordering::SeqCst), 0);
+}
@@ -0,0 +1,80 @@
+use bytes::Bytes;
+
+use super::TestContext;
+use crate::config::AppConfig;
+use crate::progress::ProgressTracker;
+use crate::s3::{is_object_key, object_key, set_s3_secret, S3Provider, S3Settings};
+use crate::storage::{ProviderKind, StorageProvider, UploadRequest, UploadSource};
+
+#[test]
+fn object_keys_are_unique_and_keep_the_file_name() {
+	let first = object_key("CloudTray/", "captura.png");
+	let second = object_key("CloudTray/", "captura.png");
+
+	assert_ne!(first, second);
+	assert!(first.starts_with("CloudTray/"));
+	assert!(first.ends_with("/captura.png"));
+}
+
+#[test]
+fn only_keys_written_by_object_key_are_eligible_for_cleanup() {
+	assert!(is_object_key("CloudTray/", &object_key("CloudTray/", "captura.png")));
+
+	assert!(!is_object_key("CloudTray/", "CloudTray/captura.png"));
+	assert!(!is_object_key("CloudTray/", "CloudTray/backup/captura.png"));
+	assert!(!is_object_key("CloudTray/", "CloudTray/20240101T120000-zzzzzzzz/captura.png"));
+	assert!(!is_object_key("CloudTray/", "CloudTray/20240101T120000-0a1b2c3d/sub/captura.png"));
+	assert!(!is_object_key("CloudTray/", "Outros/20240101T120000-0a1b2c3d/captura.png"));
+	assert!(is_object_key("CloudTray/", "CloudTray/20240101T120000-0a1b2c3d/captura.png"));
+}
+
+/// Roda contra um MinIO local (ver README): `cargo test -- --ignored`, com `CLOUDTRAY_TEST_S3_ENDPOINT`
+/// (ex.: http://localhost:9000), `CLOUDTRAY_TEST_S3_BUCKET`, `CLOUDTRAY_TEST_S3_ACCESS_KEY` e `CLOUDTRAY_TEST_S3_SECRET_KEY`.
+#[tokio::test]
+#[ignore = "requer um servidor S3 (MinIO) configurado pelas variáveis CLOUDTRAY_TEST_S3_*"]
+async fn same_name_uploads_do_not_overwrite_objects() {
+	let ctx = TestContext::new().await;
+	let env = |name: &str| std::env::var(name).unwrap_or_else(|_| panic!("{} não definida", name));
+
+	set_s3_secret(env("CLOUDTRAY_TEST_S3_SECRET_KEY")).await.unwrap();
+	let config = AppConfig {
+		provider: ProviderKind::S3,
+		s3: Some(S3Settings {
+			endpoint: env("CLOUDTRAY_TEST_S3_ENDPOINT"),
+			region: "us-east-1".to_string(),
+			bucket: env("CLOUDTRAY_TEST_S3_BUCKET"),
+			prefix: format!("cloudtray-test-{}", rand::random::<u32>()),
+			access_key_id: env("CLOUDTRAY_TEST_S3_ACCESS_KEY"),
+			force_path_style: true,
+		}),
+		..AppConfig::default()
+	};
+	let provider = S3Provider::from_config(&config, &ctx.http.client()).await.unwrap();
+	let folder = provider.ensure_folder().await.unwrap();
+
+	let mut uploaded = Vec::new();
+	for content in [&b"primeiro"[..], &b"segundo"[..]] {
+		let tracker = ProgressTracker::new(Box::new(|_| {}), "captura.png".to_string(), content.len() as u64);
+		let request = UploadRequest {
+			source: UploadSource::Memory(Bytes::from_static(content)),
+			size: content.len() as u64,
+			file_name: "captura.png".to_string(),
+			folder_id: folder.id.clone(),
+		};
+		uploaded.push(provider.upload(request, tracker).await.unwrap());
+	}
+	assert_ne!(uploaded[0].id, uploaded[1].id);
+
+	let listed = provider.list(&folder.id).await.unwrap();
+	assert_eq!(listed.len(), 2);
+	assert!(listed.iter().all(|file| file.name == "captura.png"));
+
+	// O primeiro link continua apontando para o conteúdo original
+	let first_link = provider.create_share_link(&uploaded[0]).await.unwrap();
+	let body = ctx.http.client().get(first_link).send().await.unwrap().bytes().await.unwrap();
+	assert_eq!(&body[..], b"primeiro");
+
+	for file in &uploaded {
+		provider.delete(&file.id).await.unwrap();
+	}
+}
@@ -0,0 +1,17 @@
+use super::fake_google::TEST_EMAIL;
+use super::{valid_tokens, TestContext};
+use crate::config::config_dir;
+use crate::token_store::load_tokens;
+
+#[tokio::test]
+async fn missing_encryption_key_is_an_error_not_a_new_key() {
+	let ctx = TestContext::new().await;
+	ctx.sign_in(valid_tokens()).await;
+
+	let key_file = config_dir().unwrap().join("token.key");
+	std::fs::remove_file(&key_file).unwrap();
+
+	let error = load_tokens(TEST_EMAIL).await.unwrap_err();
+	assert_eq!(error.code(), "secure_storage");
+	assert!(!key_file.exists());
+}
@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+
+use bytes::Bytes;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+
+use super::TestContext;
+use crate::config::AppConfig;
+use crate::progress::ProgressTracker;
+use crate::storage::{ProviderKind, StorageProvider, UploadRequest, UploadSource};
+use crate::webdav::{numbered_name, set_webdav_password, WebDavProvider, WebDavSettings};
+
+type Files = Arc<Mutex<HashMap<String, Bytes>>>;
+
+/// Servidor WebDAV mínimo: guarda os PUTs em memória, respeita `If-None-Match: *` e lista a pasta com PROPFIND.
+fn start_webdav(files: Files, puts: Arc<AtomicU32>) -> SocketAddr {
+	let make_service = make_service_fn(move |_| {
+		let files = Arc::clone(&files);
+		let puts = Arc::clone(&puts);
+		async move {
+			Ok::<_, Infallible>(service_fn(move |request: Request<Body>| {
+				let files = Arc::clone(&files);
+				let puts = Arc::clone(&puts);
+				async move {
+					let path = request.uri().path().to_string();
+					let create_only = request.headers().get(hyper::header::IF_NONE_MATCH).is_some();
+					if request.method().as_str() == "PROPFIND" {
+						let responses: String = files
+							.lock()
+							.unwrap()
+							.keys()
+							.filter(|file| file.starts_with(&path))
+							.map(|file| format!(
+								"<d:response><d:href>{}</d:href><d:propstat><d:prop>\
+								<d:getlastmodified>Mon, 01 Jan 2024 00:00:00 GMT</d:getlastmodified><d:resourcetype/>\
+								</d:prop></d:propstat></d:response>",
+								file
+							))
+							.collect();
+						let body = format!(r#"<?xml version="1.0"?><d:multistatus xmlns:d="DAV:">{}</d:multistatus>"#, responses);
+						return Ok::<_, Infallible>(Response::builder().status(207).body(Body::from(body)).unwrap());
+					}
+					if request.method() != Method::PUT {
+						return Ok::<_, Infallible>(Response::builder().status(StatusCode::METHOD_NOT_ALLOWED).body(Body::empty()).unwrap());
+					}
+
+					let body = hyper::body::to_bytes(request.into_body()).await.unwrap_or_default();
+					puts.fetch_add(1, Ordering::SeqCst);
+					let mut files = files.lock().unwrap();
+					let status = if create_only && files.contains_key(&path) {
+						StatusCode::PRECONDITION_FAILED
+					} else {
+						files.insert(path, body);
+						StatusCode::CREATED
+					};
+					Ok(Response::builder().status(status).body(Body::empty()).unwrap())
+				}
+			}))
+		}
+	});
+
+	let server = Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0))).serve(make_service);
+	let addr = server.local_addr();
+	tokio::spawn(server);
+	addr
+}
+
+#[test]
+fn numbered_name_keeps_the_extension() {
+	assert_eq!(numbered_name("captura.png", 0), "captura.png");
+	assert_eq!(numbered_name("captura.png", 2), "captura (2).png");
+	assert_eq!(numbered_name("LEIAME", 1), "LEIAME (1)");
+	assert_eq!(numbered_name(".env", 1), ".env (1)");
+}
+
+fn webdav_config(server_url: String, folder: &str) -> AppConfig {
+	AppConfig {
+		provider: ProviderKind::WebDav,
+		webdav: Some(WebDavSettings {
+			server_url,
+			username: "tester".to_string(),
+			dav_path: Some(String::new()),
+			folder: folder.to_string(),
+			nextcloud_shares: false,
+		}),
+		..AppConfig::default()
+	}
+}
+
+#[tokio::test]
+async fn an_empty_folder_is_rejected() {
+	let ctx = TestContext::new().await;
+	set_webdav_password("senha".to_string()).await.unwrap();
+
+	for folder in ["", "/"] {
+		let config = webdav_config("http://127.0.0.1:1".to_string(), folder);
+		let error = WebDavProvider::from_config(&config, &ctx.http.client()).await.err().unwrap();
+		assert_eq!(error.code(), "config");
+	}
+}
+
+#[tokio::test]
+async fn same_name_uploads_do_not_overwrite_files() {
+	let ctx = TestContext::new().await;
+	let files: Files = Arc::default();
+	let puts = Arc::new(AtomicU32::new(0));
+	let addr = start_webdav(Arc::clone(&files), Arc::clone(&puts));
+
+	set_webdav_password("senha".to_string()).await.unwrap();
+	let config = webdav_config(format!("http://{}", addr), "CloudTray");
+	let provider = WebDavProvider::from_config(&config, &ctx.http.client()).await.unwrap();
+
+	let mut uploaded = Vec::new();
+	for content in [&b"primeiro"[..], &b"segundo"[..]] {
+		let tracker = ProgressTracker::new(Box::new(|_| {}), "captura.png".to_string(), content.len() as u64);
+		let request = UploadRequest {
+			source: UploadSource::Memory(Bytes::from_static(content)),
+			size: content.len() as u64,
+			file_name: "captura.png".to_string(),
+			folder_id: "CloudTray".to_string(),
+		};
+		uploaded.push(provider.upload(request, tracker).await.unwrap());
+	}
+
+	assert_eq!(uploaded[0].name, "captura.png");
+	assert_eq!(uploaded[1].name, "captura (1).png");
+	// O nome livre vem da listagem; o conteúdo não é reenviado a cada colisão
+	assert_eq!(puts.load(Ordering::SeqCst), 2);
+
+	let files = files.lock().unwrap();
+	assert_eq!(files.get("/CloudTray/captura.png").map(|body| &body[..]), Some(&b"primeiro"[..]));
+	assert_eq!(files.get("/CloudTray/captura%20%281%29.png").map(|body| &body[..]), Some(&b"segundo"[..]));
+}
@@ -0,0 +1,329 @@
+use std::path::PathBuf;
+
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use keyring::Entry;
+
+use crate::config::{config_dir, isolated_config_dir, migrate_legacy_config_dir, APP_IDENTIFIER};
+use crate::drive::GoogleTokens;
+use crate::error::CloudTrayError;
+
+const KEYRING_SERVICE: &str = APP_IDENTIFIER;
+const KEYRING_USER: &str = "google_tokens";
+
+const LEGACY_TOKENS_FILE: &str = "google_tokens.json";
+const ENCRYPTED_TOKENS_FILE: &str = "google_tokens.enc";
+const ENCRYPTION_KEY_FILE: &str = "token.key";
+const NONCE_SIZE: usize = 12;
+
+fn config_path() -> Result<PathBuf, CloudTrayError> {
+	config_dir()
+}
+
+/// Nos testes os segredos ficam só no arquivo cifrado do perfil temporário, sem tocar no keyring do sistema.
+#[cfg(test)]
+fn keyring_disabled() -> Option<keyring::Error> {
+	Some(keyring::Error::NoStorageAccess("keyring desativado nos testes".into()))
+}
+
+#[cfg(not(test))]
+fn keyring_disabled() -> Option<keyring::Error> {
+	None
+}
+
+fn keyring_user(account_id: &str) -> String {
+	format!("{}:{}", KEYRING_USER, account_id)
+}
+
+fn sanitize(name: &str) -> String {
+	name.chars()
+		.map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+		.collect()
+}
+
+fn encrypted_file_name(account_id: &str) -> String {
+	format!("google_tokens_{}.enc", sanitize(account_id))
+}
+
+fn secret_user(name: &str) -> String {
+	format!("secret:{}", name)
+}
+
+fn secret_file_name(name: &str) -> String {
+	format!("secret_{}.enc", sanitize(name))
+}
+
+async fn keyring_get(user: String) -> Result<Option<String>, keyring::Error> {
+	if let Some(error) = keyring_disabled() {
+		return Err(error);
+	}
+
+	tokio::task::spawn_blocking(move || {
+		match Entry::new(KEYRING_SERVICE, &user)?.get_password() {
+			Ok(secret) => Ok(Some(secret)),
+			Err(keyring::Error::NoEntry) => Ok(None),
+			Err(e) => Err(e),
+		}
+	})
+	.await
+	.unwrap_or_else(|e| Err(keyring::Error::PlatformFailure(Box::new(e))))
+}
+
+async fn keyring_set(user: String, secret: String) -> Result<(), keyring::Error> {
+	if let Some(error) = keyring_disabled() {
+		return Err(error);
+	}
+
+	tokio::task::spawn_blocking(move || Entry::new(KEYRING_SERVICE, &user)?.set_password(&secret))
+		.await
+		.unwrap_or_else(|e| Err(keyring::Error::PlatformFailure(Box::new(e))))
+}
+
+async fn keyring_delete(user: String) -> Result<(), keyring::Error> {
+	if let Some(error) = keyring_disabled() {
+		return Err(error);
+	}
+
+	tokio::task::spawn_blocking(move || {
+		match Entry::new(KEYRING_SERVICE, &user)?.delete_credential() {
+			Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+			Err(e) => Err(e),
+		}
+	})
+	.await
+	.unwrap_or_else(|e| Err(keyring::Error::PlatformFailure(Box::new(e))))
+}
+
+/// Pasta da chave local. Fica fora da pasta de configuração (e do alcance da webview),
+/// para que os arquivos cifrados sozinhos (backup, sync) não exponham o refresh token.
+/// Num perfil isolado tudo fica na própria pasta do perfil.
+fn key_dir() -> Result<PathBuf, CloudTrayError> {
+	if let Some(dir) = isolated_config_dir() {
+		return Ok(dir);
+	}
+
+	dirs::data_local_dir()
+		.map(|dir| dir.join(KEYRING_SERVICE))
+		.ok_or_else(|| CloudTrayError::secure_storage("Não foi possível encontrar o diretório de dados local"))
+}
+
+/// Há tokens ou segredos cifrados com a chave atual; trocar a chave os tornaria ilegíveis.
+async fn has_encrypted_files() -> Result<bool, CloudTrayError> {
+	let Ok(mut entries) = tokio::fs::read_dir(config_path()?).await else {
+		return Ok(false);
+	};
+
+	while let Some(entry) = entries
+		.next_entry()
+		.await
+		.map_err(|e| CloudTrayError::from(e).with_context("Erro ao ler diretório de configuração"))?
+	{
+		if entry.file_name().to_string_lossy().ends_with(".enc") {
+			return Ok(true);
+		}
+	}
+	Ok(false)
+}
+
+/// Versões anteriores guardavam a chave junto dos arquivos cifrados; ela é movida para `key_dir`.
+async fn move_legacy_key(key_path: &PathBuf) -> Result<(), CloudTrayError> {
+	let legacy_path = config_path()?.join(ENCRYPTION_KEY_FILE);
+	if legacy_path == *key_path || key_path.exists() || !legacy_path.exists() {
+		return Ok(());
+	}
+
+	let key = tokio::fs::read(&legacy_path)
+		.await
+		.map_err(|e| CloudTrayError::from(e).with_context("Erro ao ler chave de criptografia"))?;
+	write_key(key_path, &key).await?;
+	remove_file_if_exists(legacy_path).await
+}
+
+async fn write_key(key_path: &PathBuf, key: &[u8]) -> Result<(), CloudTrayError> {
+	if let Some(parent) = key_path.parent() {
+		tokio::fs::create_dir_all(parent)
+			.await
+			.map_err(|e| CloudTrayError::from(e).with_context("Erro ao criar diretório da chave"))?;
+	}
+
+	let mut options = tokio::fs::OpenOptions::new();
+	options.write(true).create(true).truncate(true);
+	#[cfg(unix)]
+	options.mode(0o600);
+
+	let mut file = options
+		.open(key_path)
+		.await
+		.map_err(|e| CloudTrayError::from(e).with_context("Erro ao criar chave de criptografia"))?;
+	tokio::io::AsyncWriteExt::write_all(&mut file, key)
+		.await
+		.map_err(|e| CloudTrayError::from(e).with_context("Erro ao salvar chave de criptografia"))
+}
+
+/// Chave local usada quando não há keyring disponível. Só é gerada quando ainda não existe nenhum
+/// arquivo cifrado: sem a chave original eles não podem ser lidos, e uma chave nova esconderia o problema.
+async fn encryption_key() -> Result<Key<Aes256Gcm>, CloudTrayError> {
+	let key_path = key_dir()?.join(ENCRYPTION_KEY_FILE);
+	move_legacy_key(&key_path).await?;
+
+	match tokio::fs::read(&key_path).await {
+		Ok(existing) if existing.len() == 32 => return Ok(*Key::<Aes256Gcm>::from_slice(&existing)),
+		Ok(_) if has_encrypted_files().await? => {
+			return Err(CloudTrayError::secure_storage("Chave de criptografia dos tokens inválida; faça login novamente"));
+		}
+		Err(_) if has_encrypted_files().await? => {
+			return Err(CloudTrayError::secure_storage("Chave de criptografia dos tokens não encontrada; faça login novamente"));
+		}
+		_ => {}
+	}
+
+	let key = Aes256Gcm::generate_key(OsRng);
+	write_key(&key_path, key.as_slice()).await?;
+
+	Ok(key)
+}
+
+async fn read_encrypted_file(file_name: &str) -> Result<Option<String>, CloudTrayError> {
+	let path = config_path()?.join(file_name);
+	if !path.exists() {
+		return Ok(None);
+	}
+
+	let content = tokio::fs::read(&path)
+		.await
+		.map_err(|e| CloudTrayError::from(e).with_context("Erro ao ler arquivo de tokens"))?;
+	if content.len() <= NONCE_SIZE {
+		return Err(CloudTrayError::secure_storage("Arquivo de tokens corrompido"));
+	}
+
+	let cipher = Aes256Gcm::new(&encryption_key().await?);
+	let (nonce, ciphertext) = content.split_at(NONCE_SIZE);
+	let plaintext = cipher
+		.decrypt(Nonce::from_slice(nonce), ciphertext)
+		.map_err(|_| CloudTrayError::secure_storage("Não foi possível descriptografar os tokens"))?;
+
+	String::from_utf8(plaintext)
+		.map(Some)
+		.map_err(|e| CloudTrayError::secure_storage(format!("Erro ao ler arquivo de tokens: {}", e)))
+}
+
+async fn write_encrypted_file(file_name: &str, secret: &str) -> Result<(), CloudTrayError> {
+	let cipher = Aes256Gcm::new(&encryption_key().await?);
+	let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+	let ciphertext = cipher
+		.encrypt(&nonce, secret.as_bytes())
+		.map_err(|_| CloudTrayError::secure_storage("Erro ao criptografar tokens"))?;
+
+	let mut content = nonce.to_vec();
+	content.extend_from_slice(&ciphertext);
+
+	tokio::fs::write(config_path()?.join(file_name), content)
+		.await
+		.map_err(|e| CloudTrayError::from(e).with_context("Erro ao salvar arquivo de tokens"))
+}
+
+async fn remove_file_if_exists(path: PathBuf) -> Result<(), CloudTrayError> {
+	if path.exists() {
+		tokio::fs::remove_file(&path)
+			.await
+			.map_err(|e| CloudTrayError::from(e).with_context("Erro ao remover arquivo de tokens"))?;
+	}
+	Ok(())
+}
+
+fn parse_tokens(secret: &str) -> Result<GoogleTokens, CloudTrayError> {
+	serde_json::from_str(secret).map_err(|e| CloudTrayError::secure_storage(format!("Erro ao parsear tokens JSON: {}", e)))
+}
+
+async fn load_entry(user: String, file_name: &str) -> Result<Option<String>, CloudTrayError> {
+	match keyring_get(user).await {
+		Ok(Some(secret)) => Ok(Some(secret)),
+		_ => read_encrypted_file(file_name).await,
+	}
+}
+
+async fn store_entry(user: String, file_name: &str, secret: String) -> Result<(), CloudTrayError> {
+	let config_path = config_path()?;
+	tokio::fs::create_dir_all(&config_path)
+		.await
+		.map_err(|e| CloudTrayError::from(e).with_context("Erro ao criar diretório"))?;
+
+	match keyring_set(user, secret.clone()).await {
+		Ok(()) => remove_file_if_exists(config_path.join(file_name)).await,
+		Err(_) => write_encrypted_file(file_name, &secret).await,
+	}
+}
+
+async fn delete_entry(user: String, file_name: &str) -> Result<(), CloudTrayError> {
+	let keyring_result = keyring_delete(user).await;
+
+	remove_file_if_exists(config_path()?.join(file_name)).await?;
+
+	match keyring_result {
+		Ok(()) | Err(keyring::Error::NoStorageAccess(_)) | Err(keyring::Error::PlatformFailure(_)) => Ok(()),
+		Err(e) => Err(CloudTrayError::secure_storage(format!("Erro ao remover dados do keyring: {}", e))),
+	}
+}
+
+pub async fn load_tokens(account_id: &str) -> Result<Option<GoogleTokens>, CloudTrayError> {
+	let secret = load_entry(keyring_user(account_id), &encrypted_file_name(account_id)).await?;
+
+	secret.map(|secret| parse_tokens(&secret)).transpose()
+}
+
+pub async fn store_tokens(account_id: &str, tokens: &GoogleTokens) -> Result<(), CloudTrayError> {
+	let secret = serde_json::to_string(tokens)
+		.map_err(|e| CloudTrayError::internal(format!("Erro ao serializar tokens: {}", e)))?;
+
+	store_entry(keyring_user(account_id), &encrypted_file_name(account_id), secret).await
+}
+
+pub async fn delete_tokens(account_id: &str) -> Result<(), CloudTrayError> {
+	delete_entry(keyring_user(account_id), &encrypted_file_name(account_id)).await
+}
+
+/// Outros segredos do app (ex.: chave de acesso do S3), guardados do mesmo jeito que os tokens.
+pub async fn load_secret(name: &str) -> Result<Option<String>, CloudTrayError> {
+	load_entry(secret_user(name), &secret_file_name(name)).await
+}
+
+pub async fn store_secret(name: &str, secret: String) -> Result<(), CloudTrayError> {
+	store_entry(secret_user(name), &secret_file_name(name), secret).await
+}
+
+pub async fn delete_secret(name: &str) -> Result<(), CloudTrayError> {
+	delete_entry(secret_user(name), &secret_file_name(name)).await
+}
+
+/// Tokens da conta única de versões anteriores: entrada do keyring sem conta,
+/// google_tokens.enc ou o google_tokens.json em texto puro.
+pub async fn load_legacy_tokens() -> Result<Option<GoogleTokens>, CloudTrayError> {
+	migrate_legacy_config_dir().await?;
+
+	if let Ok(Some(secret)) = keyring_get(KEYRING_USER.to_string()).await {
+		return parse_tokens(&secret).map(Some);
+	}
+
+	if let Some(secret) = read_encrypted_file(ENCRYPTED_TOKENS_FILE).await? {
+		return parse_tokens(&secret).map(Some);
+	}
+
+	let legacy_path = config_path()?.join(LEGACY_TOKENS_FILE);
+	if !legacy_path.exists() {
+		return Ok(None);
+	}
+
+	let tokens_str = tokio::fs::read_to_string(&legacy_path)
+		.await
+		.map_err(|e| CloudTrayError::from(e).with_context("Erro ao ler arquivo de tokens"))?;
+
+	parse_tokens(&tokens_str).map(Some)
+}
+
+pub async fn delete_legacy_tokens() -> Result<(), CloudTrayError> {
+	let config_path = config_path()?;
+	let _ = keyring_delete(KEYRING_USER.to_string()).await;
+
+	remove_file_if_exists(config_path.join(ENCRYPTED_TOKENS_FILE)).await?;
+	remove_file_if_exists(config_path.join(LEGACY_TOKENS_FILE)).await
+}
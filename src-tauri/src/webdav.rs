@@ -0,0 +1,447 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use reqwest::header::{CONTENT_LENGTH, CONTENT_TYPE, IF_NONE_MATCH};
+use reqwest::{Method, StatusCode};
+use serde::{Deserialize, Serialize};
+
+use crate::config::AppConfig;
+use crate::drive::{mime_type_for, DriveFile, DriveFolder};
+use crate::error::CloudTrayError;
+use crate::progress::{counting_body, ProgressTracker};
+use crate::storage::{StorageProvider, UploadRequest};
+use crate::token_store;
+use crate::xml;
+
+const PASSWORD_SECRET_NAME: &str = "webdav_password";
+const SHARES_API_PATH: &str = "ocs/v2.php/apps/files_sharing/api/v1/shares";
+// Link público somente leitura
+const PUBLIC_LINK_SHARE_TYPE: &str = "3";
+const READ_PERMISSION: &str = "1";
+const MAX_LISTED_FILES: usize = 50;
+// Cópias numeradas ("nome (1).ext", "nome (2).ext"...) procuradas antes de desistir do envio
+const MAX_NAME_COPIES: u32 = 100;
+// Envios recusados com 412 porque o nome livre foi ocupado entre a listagem e o PUT
+const MAX_UPLOAD_RACES: u32 = 3;
+
+const PATH_SEGMENT: &AsciiSet = &NON_ALPHANUMERIC.remove(b'-').remove(b'_').remove(b'.').remove(b'~');
+
+const PROPFIND_BODY: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<d:propfind xmlns:d="DAV:">
+	<d:prop>
+		<d:getlastmodified/>
+		<d:resourcetype/>
+	</d:prop>
+</d:propfind>"#;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WebDavSettings {
+	/// Ex.: https://cloud.exemplo.com
+	pub server_url: String,
+	pub username: String,
+	/// Caminho do WebDAV relativo ao servidor; sem ele é usado o do Nextcloud (remote.php/dav/files/<usuário>).
+	#[serde(default)]
+	pub dav_path: Option<String>,
+	#[serde(default = "default_folder")]
+	pub folder: String,
+	/// Cria links públicos pela OCS Share API do Nextcloud. Servidores WebDAV simples devolvem a URL do próprio arquivo.
+	#[serde(default = "default_nextcloud_shares")]
+	pub nextcloud_shares: bool,
+}
+
+fn default_folder() -> String {
+	"CloudTray".to_string()
+}
+
+fn default_nextcloud_shares() -> bool {
+	true
+}
+
+struct DavEntry {
+	path: String,
+	last_modified: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OcsResponse {
+	ocs: OcsBody,
+}
+
+#[derive(Debug, Deserialize)]
+struct OcsBody {
+	meta: OcsMeta,
+	#[serde(default)]
+	data: serde_json::Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct OcsMeta {
+	statuscode: u16,
+	message: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Share {
+	path: String,
+	url: String,
+}
+
+pub struct WebDavProvider {
+	settings: WebDavSettings,
+	password: String,
+	retention_hours: i64,
+	client: reqwest::Client,
+}
+
+impl WebDavProvider {
+	pub async fn from_config(config: &AppConfig, client: &reqwest::Client) -> Result<Self, CloudTrayError> {
+		let settings = config.webdav.clone().ok_or_else(|| CloudTrayError::config("Armazenamento WebDAV não configurado"))?;
+		// Sem pasta, a limpeza pela retenção apagaria os arquivos da raiz do usuário
+		if settings.folder.trim_matches('/').is_empty() {
+			return Err(CloudTrayError::config("Defina a pasta do WebDAV; a raiz do usuário não pode ser usada"));
+		}
+		let password = token_store::load_secret(PASSWORD_SECRET_NAME)
+			.await?
+			.ok_or_else(|| CloudTrayError::config("Senha do WebDAV não configurada"))?;
+
+		Ok(Self {
+			settings,
+			password,
+			retention_hours: config.retention_hours,
+			client: client.clone(),
+		})
+	}
+
+	fn folder(&self) -> String {
+		self.settings.folder.trim_matches('/').to_string()
+	}
+
+	fn server_url(&self) -> &str {
+		self.settings.server_url.trim_end_matches('/')
+	}
+
+	fn dav_root(&self) -> String {
+		let dav_path = match &self.settings.dav_path {
+			Some(dav_path) => dav_path.trim_matches('/').to_string(),
+			None => format!("remote.php/dav/files/{}", encode_path(&self.settings.username)),
+		};
+		if dav_path.is_empty() {
+			return self.server_url().to_string();
+		}
+		format!("{}/{}", self.server_url(), dav_path)
+	}
+
+	/// URL de um caminho relativo à raiz do usuário, ex.: `CloudTray/print.png`.
+	fn file_url(&self, path: &str) -> String {
+		format!("{}/{}", self.dav_root(), encode_path(path))
+	}
+
+	fn request(&self, method: Method, url: &str) -> reqwest::RequestBuilder {
+		self.client
+			.request(method, url)
+			.basic_auth(&self.settings.username, Some(&self.password))
+	}
+
+	fn ocs_request(&self, method: Method) -> reqwest::RequestBuilder {
+		self.request(method, &format!("{}/{}", self.server_url(), SHARES_API_PATH))
+			.header("OCS-APIRequest", "true")
+			.query(&[("format", "json")])
+	}
+
+	async fn propfind(&self, folder_id: &str) -> Result<Vec<DavEntry>, CloudTrayError> {
+		let folder_url = format!("{}/", self.file_url(folder_id));
+		let response = self.request(Method::from_bytes(b"PROPFIND").unwrap(), &folder_url)
+			.header("Depth", "1")
+			.header(CONTENT_TYPE, "application/xml; charset=utf-8")
+			.body(PROPFIND_BODY)
+			.send()
+			.await
+			.map_err(|e| CloudTrayError::from(e).with_context("Erro ao listar arquivos"))?;
+		let body = check_response(response, "listar arquivos")
+			.await?
+			.text()
+			.await?;
+
+		// O href vem como caminho absoluto no servidor; o que interessa é o trecho depois da raiz do WebDAV
+		let root_path = reqwest::Url::parse(&self.dav_root())
+			.map(|url| url.path().trim_end_matches('/').to_string())
+			.map_err(|e| CloudTrayError::config(format!("URL do WebDAV inválida: {}", e)))?;
+
+		let mut entries = Vec::new();
+		for response in xml::elements(&body, "response") {
+			let is_collection = xml::elements(response, "resourcetype")
+				.first()
+				.map(|resource_type| resource_type.contains("collection"))
+				.unwrap_or(false);
+			if is_collection {
+				continue;
+			}
+
+			let Some(href) = xml::first(response, "href") else {
+				continue;
+			};
+			let href_path = reqwest::Url::parse(&href)
+				.map(|url| url.path().to_string())
+				.unwrap_or(href);
+			let Some(relative) = href_path.strip_prefix(&root_path) else {
+				continue;
+			};
+			let path = percent_decode_str(relative.trim_start_matches('/'))
+				.decode_utf8_lossy()
+				.to_string();
+
+			let last_modified = xml::first(response, "getlastmodified")
+				.and_then(|value| DateTime::parse_from_rfc2822(&value).ok())
+				.map(|value| value.with_timezone(&Utc))
+				.unwrap_or_else(Utc::now);
+
+			entries.push(DavEntry { path, last_modified });
+		}
+
+		Ok(entries)
+	}
+
+	/// Links públicos já existentes para os arquivos da pasta, indexados pelo caminho.
+	async fn existing_shares(&self, folder_id: &str) -> Result<HashMap<String, String>, CloudTrayError> {
+		let response = self.ocs_request(Method::GET)
+			.query(&[
+				("path", format!("/{}", folder_id)),
+				("subfiles", "true".to_string()),
+			])
+			.send()
+			.await
+			.map_err(|e| CloudTrayError::from(e).with_context("Erro ao buscar links"))?;
+		let shares: Vec<Share> = parse_ocs(response, "buscar links").await?;
+
+		Ok(shares
+			.into_iter()
+			.map(|share| (share.path.trim_start_matches('/').to_string(), share.url))
+			.collect())
+	}
+
+	/// O Nextcloud só aceita a data de expiração em dias; arredonda para cima e nunca usa o dia atual.
+	fn share_expiration(&self) -> String {
+		let today = Utc::now().date_naive();
+		let expires = (Utc::now() + Duration::hours(self.retention_hours)).date_naive();
+		expires.max(today + Duration::days(1)).format("%Y-%m-%d").to_string()
+	}
+}
+
+#[async_trait]
+impl StorageProvider for WebDavProvider {
+	async fn ensure_folder(&self) -> Result<DriveFolder, CloudTrayError> {
+		let folder = self.folder();
+
+		// MKCOL cria um nível por vez
+		let mut current = String::new();
+		for segment in folder.split('/').filter(|segment| !segment.is_empty()) {
+			if !current.is_empty() {
+				current.push('/');
+			}
+			current.push_str(segment);
+
+			let response = self.request(Method::from_bytes(b"MKCOL").unwrap(), &format!("{}/", self.file_url(&current)))
+				.send()
+				.await
+				.map_err(|e| CloudTrayError::from(e).with_context("Erro ao criar pasta"))?;
+
+			// 405 indica que a pasta já existe
+			if response.status() != StatusCode::METHOD_NOT_ALLOWED {
+				check_response(response, "criar pasta").await?;
+			}
+		}
+
+		Ok(DriveFolder {
+			name: folder.clone(),
+			id: folder,
+		})
+	}
+
+	async fn upload(&self, request: UploadRequest, tracker: Arc<ProgressTracker>) -> Result<DriveFile, CloudTrayError> {
+		// O nome livre ("nome (1).ext", "nome (2).ext"...) é escolhido antes do envio, para não reenviar o arquivo inteiro
+		// a cada colisão; `If-None-Match: *` só protege contra outro envio que ocupe o nome nesse meio-tempo
+		let mut taken: HashSet<String> = self
+			.propfind(&request.folder_id)
+			.await?
+			.into_iter()
+			.filter_map(|entry| entry.path.rsplit('/').next().map(str::to_string))
+			.collect();
+
+		for _ in 0..MAX_UPLOAD_RACES {
+			let file_name = (0..=MAX_NAME_COPIES)
+				.map(|copy| numbered_name(&request.file_name, copy))
+				.find(|name| !taken.contains(name))
+				.ok_or_else(|| CloudTrayError::invalid_input(format!("Já existem arquivos demais chamados \"{}\" na pasta", request.file_name)))?;
+			let path = format!("{}/{}", request.folder_id, file_name);
+			let content = request.source.stream_range(0, request.size).await?;
+
+			let response = self.request(Method::PUT, &self.file_url(&path))
+				.header(CONTENT_LENGTH, request.size)
+				.header(CONTENT_TYPE, mime_type_for(&request.file_name))
+				.header(IF_NONE_MATCH, "*")
+				.body(counting_body(content, Arc::clone(&tracker)))
+				.send()
+				.await
+				.map_err(|e| CloudTrayError::from(e).with_context("Erro ao enviar arquivo"))?;
+
+			if response.status() == StatusCode::PRECONDITION_FAILED {
+				taken.insert(file_name);
+				tracker.set_position(0);
+				continue;
+			}
+			check_response(response, "enviar arquivo").await?;
+
+			return Ok(DriveFile {
+				id: path,
+				name: file_name,
+				web_view_link: String::new(),
+			});
+		}
+
+		Err(CloudTrayError::unexpected_response(format!("O nome \"{}\" foi ocupado por outro envio várias vezes seguidas", request.file_name)))
+	}
+
+	async fn list(&self, folder_id: &str) -> Result<Vec<DriveFile>, CloudTrayError> {
+		let mut entries = self.propfind(folder_id).await?;
+		entries.sort_by_key(|entry| std::cmp::Reverse(entry.last_modified));
+		entries.truncate(MAX_LISTED_FILES);
+
+		let shares = if self.settings.nextcloud_shares {
+			self.existing_shares(folder_id).await?
+		} else {
+			HashMap::new()
+		};
+
+		Ok(entries
+			.into_iter()
+			.map(|entry| {
+				let web_view_link = match shares.get(&entry.path) {
+					Some(url) => url.clone(),
+					None if self.settings.nextcloud_shares => String::new(),
+					None => self.file_url(&entry.path),
+				};
+
+				DriveFile {
+					name: entry.path.rsplit('/').next().unwrap_or(&entry.path).to_string(),
+					id: entry.path,
+					web_view_link,
+				}
+			})
+			.collect())
+	}
+
+	async fn delete(&self, file_id: &str) -> Result<(), CloudTrayError> {
+		// Excluir o arquivo também remove os compartilhamentos dele no Nextcloud
+		let response = self.request(Method::DELETE, &self.file_url(file_id))
+			.send()
+			.await
+			.map_err(|e| CloudTrayError::from(e).with_context("Erro ao apagar arquivo"))?;
+
+		if response.status() != StatusCode::NOT_FOUND {
+			check_response(response, "remover arquivo").await?;
+		}
+
+		Ok(())
+	}
+
+	async fn create_share_link(&self, file: &DriveFile) -> Result<String, CloudTrayError> {
+		if !self.settings.nextcloud_shares {
+			return Ok(self.file_url(&file.id));
+		}
+
+		let response = self.ocs_request(Method::POST)
+			.form(&[
+				("path", format!("/{}", file.id)),
+				("shareType", PUBLIC_LINK_SHARE_TYPE.to_string()),
+				("permissions", READ_PERMISSION.to_string()),
+				("expireDate", self.share_expiration()),
+			])
+			.send()
+			.await
+			.map_err(|e| CloudTrayError::from(e).with_context("Erro ao criar link público"))?;
+		let share: Share = parse_ocs(response, "criar link público").await?;
+
+		Ok(share.url)
+	}
+
+	/// Limpa sempre a pasta configurada, nunca a informada pelo frontend (que pode vir vazia e apontar para a raiz).
+	async fn delete_expired(&self, _folder_id: &str, older_than: DateTime<Utc>) -> Result<(), CloudTrayError> {
+		let entries = self.propfind(&self.folder()).await?;
+
+		for entry in entries.iter().filter(|entry| entry.last_modified < older_than) {
+			self.delete(&entry.path).await?;
+		}
+
+		Ok(())
+	}
+}
+
+fn encode_path(path: &str) -> String {
+	path.split('/')
+		.map(|segment| utf8_percent_encode(segment, PATH_SEGMENT).to_string())
+		.collect::<Vec<_>>()
+		.join("/")
+}
+
+async fn check_response(response: reqwest::Response, action: &str) -> Result<reqwest::Response, CloudTrayError> {
+	if response.status().is_success() {
+		return Ok(response);
+	}
+
+	let status = response.status();
+	let body = response.text().await.unwrap_or_default();
+	let message = xml::first(&body, "message").unwrap_or(body);
+	Err(CloudTrayError::from_status(status.as_u16(), None, format!("Erro no WebDAV ao {}: {}", action, message)))
+}
+
+async fn parse_ocs<T>(response: reqwest::Response, action: &str) -> Result<T, CloudTrayError>
+where
+	T: serde::de::DeserializeOwned,
+{
+	let status = response.status();
+	let response_text = response.text().await?;
+
+	let ocs: OcsResponse = serde_json::from_str(&response_text).map_err(|e| {
+		let message = format!("Erro ao {}: {}", action, e);
+		if status.is_success() {
+			CloudTrayError::unexpected_response(message)
+		} else {
+			CloudTrayError::from_status(status.as_u16(), None, message)
+		}
+	})?;
+
+	// A API OCS v2 usa os mesmos códigos do HTTP em meta.statuscode
+	if !(200..300).contains(&ocs.ocs.meta.statuscode) {
+		return Err(CloudTrayError::from_status(
+			ocs.ocs.meta.statuscode,
+			None,
+			format!("Erro ao {}: {}", action, ocs.ocs.meta.message.unwrap_or_default()),
+		));
+	}
+
+	serde_json::from_value(ocs.ocs.data)
+		.map_err(|e| CloudTrayError::unexpected_response(format!("Erro ao {}: resposta inesperada: {}", action, e)))
+}
+
+pub async fn set_webdav_password(password: String) -> Result<(), CloudTrayError> {
+	if password.is_empty() {
+		token_store::delete_secret(PASSWORD_SECRET_NAME).await
+	} else {
+		token_store::store_secret(PASSWORD_SECRET_NAME, password).await
+	}
+}
+
+/// "captura.png" vira "captura (2).png" na cópia 2; a cópia 0 mantém o nome original.
+pub(crate) fn numbered_name(file_name: &str, copy: u32) -> String {
+	if copy == 0 {
+		return file_name.to_string();
+	}
+
+	match file_name.rsplit_once('.') {
+		Some((stem, extension)) if !stem.is_empty() => format!("{} ({}).{}", stem, copy, extension),
+		_ => format!("{} ({})", file_name, copy),
+	}
+}
@@ -0,0 +1,46 @@
+/// Conteúdo de cada elemento `name`, com ou sem prefixo de namespace (`<d:href>`, `<D:href>`, `<href>`).
+/// Elementos vazios como `<d:collection/>` não entram no resultado.
+pub fn elements<'a>(xml: &'a str, name: &str) -> Vec<&'a str> {
+	let mut values = Vec::new();
+	let mut rest = xml;
+
+	while let Some(start) = rest.find('<') {
+		let after_start = &rest[start + 1..];
+		let Some(tag_end) = after_start.find('>') else {
+			break;
+		};
+		let tag = &after_start[..tag_end];
+		rest = &after_start[tag_end + 1..];
+
+		if tag.starts_with(['/', '?', '!']) || tag.ends_with('/') {
+			continue;
+		}
+
+		let full_name = tag.split_whitespace().next().unwrap_or_default();
+		if full_name.rsplit(':').next() != Some(name) {
+			continue;
+		}
+
+		let close = format!("</{}>", full_name);
+		let Some(end) = rest.find(&close) else {
+			break;
+		};
+		values.push(&rest[..end]);
+		rest = &rest[end + close.len()..];
+	}
+
+	values
+}
+
+pub fn first(xml: &str, name: &str) -> Option<String> {
+	elements(xml, name).first().map(|value| unescape(value))
+}
+
+pub fn unescape(value: &str) -> String {
+	value
+		.replace("&lt;", "<")
+		.replace("&gt;", ">")
+		.replace("&quot;", "\"")
+		.replace("&apos;", "'")
+		.replace("&amp;", "&")
+}
@@ -8,7 +8,7 @@ import { open as openShell } from '@tauri-apps/api/shell';
 
 import "./App.css";
 
-import Settings from "./tabs/settings/Settings";
+import Settings, { AppConfig } from "./tabs/settings/Settings";
 import Recents from "./tabs/Recents/Recents";
 import Button from "./components/Button";
 import Separator from "./components/Separator";
@@ -18,6 +18,7 @@ import fileSearch from "./assets/file-search.svg";
 import google from "./assets/google.svg";
 import About from "./tabs/About/About";
 import { useTranslation } from "react-i18next";
+import { errorMessage } from "./errors";
 
 const GOOGLE_CLIENT_ID = import.meta.env.VITE_GOOGLE_CLIENT_ID;
 const GOOGLE_CLIENT_SECRET = import.meta.env.VITE_GOOGLE_CLIENT_SECRET;
@@ -28,12 +29,61 @@ interface GoogleTokens {
 	refresh_token: string;
 	expires_in: number;
 	token_type: string;
+	expires_at?: number;
+	scope?: string;
+}
+
+interface Account {
+	email: string;
+	name: string | null;
+	picture: string | null;
+	broader_scopes: string[];
+}
+
+interface LogoutReport {
+	account_id: string | null;
+	tokens_revoked: boolean;
+	folder_trashed: boolean;
+	failures: unknown[];
+}
+
+interface AuthRequiredEvent {
+	account_id: string;
+	reason: string;
+	message: string;
 }
 
 interface UploadProgress {
 	[key: string]: number;
 }
 
+interface UploadProgressEvent {
+	file_name: string;
+	progress: number;
+	bytes_sent: number;
+	total_bytes: number;
+	bytes_per_second: number;
+	eta_seconds: number | null;
+}
+
+interface UploadStats {
+	[key: string]: { bytesPerSecond: number, etaSeconds: number | null };
+}
+
+const formatRate = (bytesPerSecond: number) => {
+	if (bytesPerSecond >= 1024 * 1024) {
+		return `${(bytesPerSecond / (1024 * 1024)).toFixed(1)} MB/s`;
+	}
+	return `${Math.round(bytesPerSecond / 1024)} KB/s`;
+};
+
+const formatEta = (seconds: number) => {
+	if (seconds >= 60) {
+		return `${Math.floor(seconds / 60)}m ${seconds % 60}s`;
+	}
+	return `${seconds}s`;
+};
+
 const App = () => {
 	const { t } = useTranslation();
 
@@ -42,6 +92,7 @@ const App = () => {
 	const [checkingAuth, setCheckingAuth] = useState(false);
 	const [isAuthenticated, setIsAuthenticated] = useState(false);
 	const [uploadProgress, setUploadProgress] = useState<UploadProgress>({});
+	const [uploadStats, setUploadStats] = useState<UploadStats>({});
 	const [uploadFeedback, setUploadFeedback] = useState<{ type: 'success' | 'error', message: string } | null>(null);
 	const [copiedId, setCopiedId] = useState<string | null>(null);
 	const [tab, setTab] = useState<'upload' | 'settings' | 'recents' | 'about'>('upload');
@@ -57,12 +108,16 @@ const App = () => {
 			});
 			setUploadProgress(initialProgress);
 	
-			const unlisten = await listen<[string, number]>("upload-progress", (event) => {
-				const [fileName, progress] = event.payload;
+			const unlisten = await listen<UploadProgressEvent>("upload-progress", (event) => {
+				const { file_name: fileName, progress, bytes_per_second, eta_seconds } = event.payload;
 				setUploadProgress(prev => ({
 					...prev,
 					[fileName]: progress
 				}));
+				setUploadStats(prev => ({
+					...prev,
+					[fileName]: { bytesPerSecond: bytes_per_second, etaSeconds: eta_seconds }
+				}));
 			});
 
 			const isSingleFile = acceptedFiles.length === 1;
@@ -96,11 +151,13 @@ const App = () => {
 	
 			setTimeout(() => {
 				setUploadProgress({});
+				setUploadStats({});
 				setUploadFeedback(null);
 			}, 5000);
 		} catch (error) {
 			setUploadProgress({});
-			setUploadFeedback({ type: 'error', message: t('app.uploadError') });
+			setUploadStats({});
+			setUploadFeedback({ type: 'error', message: errorMessage(error, t) });
 			setTimeout(() => {
 				setUploadFeedback(null);
 			}, 5000);
@@ -147,12 +204,16 @@ const App = () => {
 				});
 				setUploadProgress(initialProgress);
 	
-				const unlisten = await listen<[string, number]>("upload-progress", (event) => {
-					const [fileName, progress] = event.payload;
+				const unlisten = await listen<UploadProgressEvent>("upload-progress", (event) => {
+					const { file_name: fileName, progress, bytes_per_second, eta_seconds } = event.payload;
 					setUploadProgress(prev => ({
 						...prev,
 						[fileName]: progress
 					}));
+					setUploadStats(prev => ({
+						...prev,
+						[fileName]: { bytesPerSecond: bytes_per_second, etaSeconds: eta_seconds }
+					}));
 				});
 
 				const isSingleFile = selectedFiles.length === 1;
@@ -173,7 +234,7 @@ const App = () => {
 						}
 					}
 					catch (error) {
-						setUploadFeedback({ type: 'error', message: t('app.uploadError') });
+						setUploadFeedback({ type: 'error', message: errorMessage(error, t) });
 					}
 				}
 	
@@ -190,53 +251,74 @@ const App = () => {
 
 				setTimeout(() => {
 					setUploadProgress({});
+					setUploadStats({});
 					setUploadFeedback(null);
 				}, 5000);
 			}
 		} catch (err) {
 			setUploadProgress({});
-			setUploadFeedback({ type: 'error', message: t('app.uploadError') });
+			setUploadStats({});
+			setUploadFeedback({ type: 'error', message: errorMessage(err, t) });
 			setTimeout(() => {
 				setUploadFeedback(null);
 			}, 5000);
 		}
 	};
 
+	const showLoginError = (error: unknown) => {
+		setUploadFeedback({ type: 'error', message: errorMessage(error, t, 'app.loginFailed') });
+		setTimeout(() => {
+			setUploadFeedback(null);
+		}, 5000);
+	};
+
 	const handleGoogleLogin = async () => {
 		try {
 			setCheckingAuth(true);
+
+			if (!GOOGLE_CLIENT_ID) {
+				throw new Error("Google credentials not configured");
+			}
+
+			await invoke("set_google_credentials", {
+				clientId: GOOGLE_CLIENT_ID,
+				clientSecret: GOOGLE_CLIENT_SECRET
+			});
+
 			const port = await invoke<number>("start_oauth_server");
 			const redirectUri = `${GOOGLE_REDIRECT_URI}:${port}`;
 
+			const unlistenError = await listen("oauth_callback_error", async () => {
+				setCheckingAuth(false);
+				await emit("open");
+				unlisten();
+				unlistenError();
+			});
+
 			const unlisten = await listen("oauth_callback", async (event: any) => {
 				const url = new URL(event.payload);
 				const code = url.searchParams.get("code");
 				
 				if (code) {
 					try {
-						if (!GOOGLE_CLIENT_ID || !GOOGLE_CLIENT_SECRET) {
-							throw new Error("Google credentials not configured");
-						}
-						
-						await invoke("set_google_credentials", {
-							clientId: GOOGLE_CLIENT_ID,
-							clientSecret: GOOGLE_CLIENT_SECRET
-						});
-						
-						const tokens = await invoke<GoogleTokens>("exchange_auth_code", {
-							code,
-							redirectUri
-						});
+						const tokens = await invoke<GoogleTokens>("exchange_auth_code", { code });
 			
 						try {
-							await invoke("save_tokens", { tokens });
+							const account = await invoke<Account>("save_tokens", { tokens });
 							setIsAuthenticated(true);
+							if (account.broader_scopes.length > 0) {
+								setUploadFeedback({ type: 'error', message: t('app.broaderScopesGranted') });
+								setTimeout(() => {
+									setUploadFeedback(null);
+								}, 5000);
+							}
 							await emit("open");
 						} catch (error) {
-							console.error("Erro ao salvar tokens:", error);
+							showLoginError(error);
 							await emit("open");
 						}
 					} catch (error) {
+						showLoginError(error);
 						await emit("open");
 					}
 				} else {
@@ -245,20 +327,15 @@ const App = () => {
 				
 				setCheckingAuth(false);
 				unlisten();
+				unlistenError();
 			});
 
-			const scope = encodeURIComponent("https://www.googleapis.com/auth/drive.file");
-			const authUrl = `https://accounts.google.com/o/oauth2/v2/auth?` +
-				`client_id=${GOOGLE_CLIENT_ID}&` +
-				`redirect_uri=${encodeURIComponent(redirectUri)}&` +
-				`response_type=code&` +
-				`scope=${scope}&` +
-				`access_type=offline`;
+			const authUrl = await invoke<string>("build_authorization_url", { redirectUri });
 
 			await emit("close");
 			await openShell(authUrl);
 		} catch (error) {
-			console.error("Erro na autenticação:", error);
+			showLoginError(error);
 			setCheckingAuth(false);
 		}
 	};
@@ -275,7 +352,11 @@ const App = () => {
 					clientSecret: GOOGLE_CLIENT_SECRET
 				});
 
-				await invoke("get_tokens");
+				// Provedores fora do Google Drive usam credenciais próprias e não passam pelo login
+				const config = await invoke<AppConfig>("load_or_create_config");
+				if (config.provider === 'google_drive') {
+					await invoke("get_tokens");
+				}
 				setIsAuthenticated(true);
 				setCheckingAuth(false);
 			} catch (error) {
@@ -291,9 +372,51 @@ const App = () => {
 		checkAuth();
 	}, []);
 
+	// O backend avisa quando o Google recusa o refresh token; os tokens já foram descartados e só resta um novo login
+	useEffect(() => {
+		const unlisten = listen<AuthRequiredEvent>("auth-required", (event) => {
+			console.error(`[${event.payload.reason}]`, event.payload.message);
+			setIsAuthenticated(false);
+			setCheckingAuth(false);
+			setUploadProgress({});
+			setUploadStats({});
+			setUploadFeedback({ type: 'error', message: t('app.sessionRevoked') });
+			setTimeout(() => {
+				setUploadFeedback(null);
+			}, 5000);
+		});
+
+		return () => {
+			unlisten.then((unlistenFn) => unlistenFn());
+		};
+	}, []);
+
 	const handleLogout = async () => {
-		await invoke("logout");
-		setIsAuthenticated(false);
+		// A conta sai do app mesmo que o Google não confirme a revogação; nesse caso o usuário é avisado
+		let report: LogoutReport;
+		try {
+			report = await invoke<LogoutReport>("logout");
+		} catch (error) {
+			// Conta desconhecida ou dados ilegíveis: nada foi revogado, então a conta continua conectada
+			setUploadFeedback({ type: 'error', message: errorMessage(error, t, 'app.logoutFailed') });
+			setTimeout(() => {
+				setUploadFeedback(null);
+			}, 5000);
+			return;
+		}
+
+		if (report.failures.length > 0) {
+			setUploadFeedback({ type: 'error', message: t('app.logoutRevokeFailed') });
+			setTimeout(() => {
+				setUploadFeedback(null);
+			}, 5000);
+		}
+
+		try {
+			await invoke("get_tokens");
+		} catch (error) {
+			setIsAuthenticated(false);
+		}
 	};
 
 	const handleCopyLink = async (link: string, id: string) => {
@@ -329,6 +452,12 @@ const App = () => {
 				</div>
 			)}
 
+			{!isAuthenticated && uploadFeedback && (
+				<div className={`upload-feedback ${uploadFeedback.type}`}>
+					{uploadFeedback.message}
+				</div>
+			)}
+
 			{!isAuthenticated && checkingAuth && (
 				<div className="container-login-google">
 					<span>{t('app.checkingAuth')}</span>
@@ -364,6 +493,12 @@ const App = () => {
 													style={{ width: `${progress}%` }}
 												/>
 											</div>
+											{progress < 100 && uploadStats[fileName]?.bytesPerSecond > 0 && (
+												<span className="upload-rate">
+													{formatRate(uploadStats[fileName].bytesPerSecond)}
+													{uploadStats[fileName].etaSeconds !== null && ` · ${formatEta(uploadStats[fileName].etaSeconds!)}`}
+												</span>
+											)}
 										</div>
 									))}
 								</div>
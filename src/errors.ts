@@ -0,0 +1,23 @@
+import { TFunction } from 'i18next';
+
+// Formato serializado do CloudTrayError devolvido pelos comandos do Tauri
+export interface CommandError {
+  code: string;
+  status: number | null;
+  reason: string | null;
+  message: string;
+}
+
+export function isCommandError(error: unknown): error is CommandError {
+  return typeof error === 'object' && error !== null && typeof (error as CommandError).code === 'string';
+}
+
+export function errorMessage(error: unknown, t: TFunction, fallbackKey = 'app.uploadError'): string {
+  if (isCommandError(error)) {
+    console.error(`[${error.code}]`, error.message);
+    return t(`errors.${error.code}`, { defaultValue: t(fallbackKey) });
+  }
+
+  console.error(error);
+  return t(fallbackKey);
+}
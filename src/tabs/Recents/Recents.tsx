@@ -5,6 +5,7 @@ import back from "../../assets/arrow-back.svg";
 
 import "./Recents.css";
 import { useTranslation } from "react-i18next";
+import { errorMessage } from "../../errors";
 
 interface RecentFile {
     id: string;
@@ -28,6 +29,7 @@ const Recents = ({
 	const { t } = useTranslation();
 
     const [loading, setLoading] = useState(true);
+    const [error, setError] = useState<string | null>(null);
 
     let oldRecentFiles = recentFiles;
 
@@ -40,7 +42,7 @@ const Recents = ({
 
             await invoke('delete_file', { fileId });
         } catch (error) {
-            console.error('Error deleting file:', error);
+            setError(errorMessage(error, t, 'recents.deleteFailed'));
             setRecentFiles(oldRecentFiles);
         }
     };
@@ -49,12 +51,13 @@ const Recents = ({
         const fetchRecentFiles = async () => {
             try {
                 setLoading(true);
+                setError(null);
 
                 const files = await invoke<RecentFile[]>("list_recent_files");
 
                 setRecentFiles(files);
             } catch (error) {
-                console.error('Error fetching recent files:', error);
+                setError(errorMessage(error, t, 'recents.loadFailed'));
             } finally {
                 setLoading(false);
             }
@@ -109,11 +112,17 @@ const Recents = ({
                 </div>
             )}
 
-            {recentFiles.length === 0 && !loading && (
+            {recentFiles.length === 0 && !loading && !error && (
                 <div className="loading-container">
                     <p>{t('recents.noRecentUploads')}</p>
                 </div>
             )}
+
+            {error && (
+                <div className="upload-feedback error">
+                    {error}
+                </div>
+            )}
         </main>
     );
 };
@@ -3,8 +3,46 @@ import { useTranslation } from 'react-i18next';
 
 import './Settings.css';
 
+interface DriveFolder {
+    id: string;
+    name: string;
+}
+
 export interface AppConfig {
     retention_hours: number;
+    max_parallel_uploads: number;
+    provider: 'google_drive' | 's3' | 'webdav';
+    s3?: {
+        endpoint: string;
+        region: string;
+        bucket: string;
+        prefix: string;
+        access_key_id: string;
+        force_path_style: boolean;
+    } | null;
+    webdav?: {
+        server_url: string;
+        username: string;
+        dav_path?: string | null;
+        folder: string;
+        nextcloud_shares: boolean;
+    } | null;
+    drive_folders?: Record<string, string>;
+    network?: {
+        proxy_url?: string | null;
+        no_proxy: string[];
+        proxy_username?: string | null;
+        ca_bundle_path?: string | null;
+    };
+    google_endpoints?: {
+        auth_url?: string;
+        token_url?: string;
+        device_code_url?: string;
+        revoke_url?: string;
+        userinfo_url?: string;
+        drive_api_url?: string;
+        drive_upload_url?: string;
+    } | null;
 }
 
 import { invoke } from '@tauri-apps/api/tauri';
@@ -17,12 +55,16 @@ import google from '../../assets/google.svg';
 
 import Separator from '../../components/Separator';
 import Button from '../../components/Button';
+import { errorMessage } from '../../errors';
 
 const Settings = ({ setTab }: { setTab: (tab: 'upload' | 'settings' | 'recents') => void }) => {
 	const { t, i18n } = useTranslation();
 
     const [config, setConfig] = useState<AppConfig | null>(null);
     const [folderUrl, setFolderUrl] = useState<string>('');
+    const [folderId, setFolderId] = useState<string>('');
+    const [folders, setFolders] = useState<DriveFolder[]>([]);
+    const [error, setError] = useState<string | null>(null);
 
     useEffect(() => {
         loadConfig();
@@ -34,26 +76,40 @@ const Settings = ({ setTab }: { setTab: (tab: 'upload' | 'settings' | 'recents')
             const savedConfig = await invoke<AppConfig>('load_or_create_config');
             setConfig(savedConfig);
         } catch (error) {
-            console.error('Erro ao carregar configuração:', error);
+            setError(errorMessage(error, t, 'settings.loadFailed'));
         }
     };
 
     const loadFolderUrl = async () => {
         try {
-            const folder = await invoke<{ id: string }>('get_or_create_app_folder');
+            const folder = await invoke<DriveFolder>('get_or_create_app_folder');
+            setFolderId(folder.id);
+            setFolderUrl(`https://drive.google.com/drive/folders/${folder.id}`);
+            setFolders(await invoke<DriveFolder[]>('list_drive_folders'));
+        } catch (error) {
+            setError(errorMessage(error, t, 'settings.folderFailed'));
+        }
+    };
+
+    const handleFolderChange = async (id: string) => {
+        try {
+            const folder = await invoke<DriveFolder>('select_drive_folder', { folder: id });
+            setFolderId(folder.id);
             setFolderUrl(`https://drive.google.com/drive/folders/${folder.id}`);
+            setError(null);
         } catch (error) {
-            console.error('Erro ao carregar URL da pasta:', error);
+            setError(errorMessage(error, t, 'settings.folderFailed'));
         }
     };
 
     const handleRetentionChange = async (hours: number) => {
         try {
-            const newConfig: AppConfig = { retention_hours: hours };
+            const newConfig: AppConfig = { ...config!, retention_hours: hours };
             await invoke('save_config', { config: newConfig });
             setConfig(newConfig);
+            setError(null);
         } catch (error) {
-            console.error('Erro ao atualizar configuração:', error);
+            setError(errorMessage(error, t, 'settings.saveFailed'));
         }
     };
 
@@ -76,7 +132,9 @@ const Settings = ({ setTab }: { setTab: (tab: 'upload' | 'settings' | 'recents')
         { value: 72, label: t('settings.threeDays') },
     ];
 
-    if (!config) return null;
+    if (!config) {
+        return error ? <div className="upload-feedback error">{error}</div> : null;
+    }
 
     return (
         <main className="container container-settings">
@@ -101,6 +159,33 @@ const Settings = ({ setTab }: { setTab: (tab: 'upload' | 'settings' | 'recents')
                 </select>
             </div>
 
+            {config.provider === 'google_drive' && folders.length > 0 && (
+                <>
+                    <div className="select-container">
+                        <label className="select-label">{t('settings.destinationFolder')}:</label>
+                        <select
+                            value={folderId}
+                            onChange={(e) => handleFolderChange(e.target.value)}
+                            className="select-select"
+                            disabled={folders.length < 2}
+                        >
+                            {folders.map(folder => (
+                                <option key={folder.id} value={folder.id}>
+                                    {folder.name}
+                                </option>
+                            ))}
+                        </select>
+                    </div>
+                    <p className="select-hint">{t('settings.destinationFolderHint')}</p>
+                </>
+            )}
+
+            {error && (
+                <div className="upload-feedback error">
+                    {error}
+                </div>
+            )}
+
             <Separator />
 
             <Button